@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// SPDX-FileCopyrightText: 2023 Jakub Jirutka <jakub@jirutka.cz>
+
+//! Sources of values for options that haven't been provided on the command
+//! line, such as configuration files.
+
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+
+use crate::error::Error;
+
+/// A source of values for options and switches that haven't been provided on
+/// the command line (nor in their environment variables), such as a
+/// configuration file. See [`FromArgs::from_args_with_config`].
+///
+/// The keys are the long names of the options without the leading dashes
+/// (e.g. `pilot-nickname` for `--pilot-nickname`). The values are parsed the
+/// same way as on the command line, switches accept `1`, `true`, `yes`, `on`
+/// or `0`, `false`, `no`, `off`.
+///
+/// [`FromArgs::from_args_with_config`]: crate::FromArgs::from_args_with_config
+pub trait ConfigSource {
+    /// Returns the name of this source to be reported in error messages (e.g.
+    /// a file path).
+    fn name(&self) -> &str;
+
+    /// Returns the values of the given key, or an empty vector if the key is
+    /// not present. Only repeating options may have more than one value.
+    fn values(&self, key: &str) -> Vec<Cow<'_, str>>;
+}
+
+/// A simple configuration in the `key = value` format.
+///
+/// Each line contains one entry, leading and trailing whitespace around the key
+/// and the value is ignored, and the value may be enclosed in double quotes.
+/// Empty lines and lines starting with `#` or `;` are ignored. The same key
+/// can be specified multiple times for repeating options.
+///
+/// ```
+/// # use argp::config::{ConfigSource, KeyValueConfig};
+/// let config = KeyValueConfig::parse("app.conf", r#"
+/// ; Nickname of the pilot.
+/// pilot-nickname = "Wes"
+/// height = 5
+/// "#).unwrap();
+///
+/// assert_eq!(config.values("height"), ["5"]);
+/// ```
+#[derive(Debug, Default, PartialEq)]
+pub struct KeyValueConfig {
+    name: String,
+    entries: Vec<(String, String)>,
+}
+
+impl KeyValueConfig {
+    /// Parses the configuration from the given `content`. The `name` is
+    /// reported in error messages.
+    pub fn parse(name: impl Into<String>, content: &str) -> Result<Self, Error> {
+        let name = name.into();
+        let mut entries = Vec::new();
+
+        for (lineno, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) if !key.trim().is_empty() => (key.trim(), value.trim()),
+                _ => {
+                    return Err(Error::Config {
+                        source: name,
+                        line: Some(lineno + 1),
+                        msg: format!("expected 'key = value', got '{}'", line),
+                    })
+                }
+            };
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+
+            entries.push((key.to_owned(), value.to_owned()));
+        }
+
+        Ok(Self { name, entries })
+    }
+
+    /// Reads and parses the configuration file at the given `path`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| Error::Config {
+            source: path.display().to_string(),
+            line: None,
+            msg: e.to_string(),
+        })?;
+
+        Self::parse(path.display().to_string(), &content)
+    }
+}
+
+impl ConfigSource for KeyValueConfig {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn values(&self, key: &str) -> Vec<Cow<'_, str>> {
+        self.entries
+            .iter()
+            .filter(|(k, _)| k == key)
+            .map(|(_, v)| Cow::from(v.as_str()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_key_value_config_parse() {
+        let config = KeyValueConfig::parse(
+            "test.conf",
+            "# comment\n; comment\n\n  foo = bar baz \nquoted=\"  x \"\nfoo=2\nempty =\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.name(), "test.conf");
+        assert_eq!(config.values("foo"), ["bar baz", "2"]);
+        assert_eq!(config.values("quoted"), ["  x "]);
+        assert_eq!(config.values("empty"), [""]);
+        assert!(config.values("missing").is_empty());
+    }
+
+    #[test]
+    fn test_key_value_config_parse_invalid() {
+        assert_eq!(
+            KeyValueConfig::parse("test.conf", "foo = 1\nbar\n"),
+            Err(Error::Config {
+                source: "test.conf".to_owned(),
+                line: Some(2),
+                msg: "expected 'key = value', got 'bar'".to_owned(),
+            })
+        );
+        assert_eq!(
+            KeyValueConfig::parse("test.conf", " = 1"),
+            Err(Error::Config {
+                source: "test.conf".to_owned(),
+                line: Some(1),
+                msg: "expected 'key = value', got '= 1'".to_owned(),
+            })
+        );
+        assert_eq!(
+            KeyValueConfig::parse("test.conf", "bar")
+                .unwrap_err()
+                .to_string(),
+            "Error parsing configuration 'test.conf' on line 1: expected 'key = value', got 'bar'."
+        );
+    }
+
+    #[test]
+    fn test_key_value_config_from_file_missing() {
+        match KeyValueConfig::from_file("/nonexistent/test.conf") {
+            Err(Error::Config { source, line, .. }) => {
+                assert_eq!(source, "/nonexistent/test.conf");
+                assert_eq!(line, None);
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+}
@@ -3,10 +3,41 @@
 
 use std::ffi::OsString;
 use std::fmt::{self, Write as _};
+use std::path::PathBuf;
 
 /// The error type for the argp parser.
 #[derive(Debug, PartialEq)]
 pub enum Error {
+    /// An abbreviated long option matches more than one option.
+    AmbiguousOption {
+        /// The abbreviated option given by the user.
+        arg: String,
+        /// The options that start with `arg`.
+        candidates: Vec<&'static str>,
+    },
+
+    /// Error reading or parsing a configuration source (see
+    /// [`crate::config`]).
+    Config {
+        /// The name of the configuration source (e.g. a file path).
+        source: String,
+        /// The line number (starting from 1) with a syntax error, or `None` if
+        /// the source could not be read.
+        line: Option<usize>,
+        /// The error message.
+        msg: String,
+    },
+
+    /// Two options or switches that cannot be used together were provided,
+    /// either because of the `conflicts_with` attribute, or because they are
+    /// in the same group that doesn't allow multiple options.
+    ConflictingOptions {
+        /// The option name (e.g. `--foo`).
+        arg: String,
+        /// The name of the option that conflicts with `arg`.
+        other: String,
+    },
+
     /// Duplicate value for a non-repeating option. The contained `String` is
     /// the option name (e.g. `--foo`).
     DuplicateOption(String),
@@ -14,6 +45,15 @@ pub enum Error {
     /// No value provided for the specified option.
     MissingArgValue(String),
 
+    /// Not enough values provided for the specified option that takes a fixed
+    /// number of values (e.g. `--rename <old> <new>`).
+    MissingArgValues {
+        /// The option name (e.g. `--foo`).
+        arg: String,
+        /// The number of values the option takes.
+        expected: usize,
+    },
+
     /// Missing required positional argument(s), option(s) or subcommand(s).
     MissingRequirements(MissingRequirements),
 
@@ -30,9 +70,56 @@ pub enum Error {
         msg: String,
     },
 
+    /// Error parsing the value of a key in the configuration source that an
+    /// option falls back to.
+    ParseConfigValue {
+        /// The name of the configuration source (e.g. a file path).
+        source: String,
+        /// The key in the configuration source.
+        key: String,
+        /// The value that failed to be parsed.
+        value: OsString,
+        /// The error message from the value parser.
+        msg: String,
+    },
+
+    /// Error parsing the value of the environment variable that an option
+    /// falls back to.
+    ParseEnvVar {
+        /// The name of the environment variable.
+        var: String,
+        /// The value of the environment variable that failed to be parsed.
+        value: OsString,
+        /// The error message from the value parser.
+        msg: String,
+    },
+
+    /// Error reading or parsing a response file (`@file` argument).
+    ResponseFile {
+        /// The path of the response file.
+        path: PathBuf,
+        /// The error message.
+        msg: String,
+    },
+
+    /// A value was attached to an option that doesn't take a value (e.g.
+    /// `--verbose=yes`). The contained `String` is the option name.
+    UnexpectedArgValue(String),
+
     /// Unknown argument.
     UnknownArgument(OsString),
 
+    /// Unknown argument that is similar to a known option or subcommand. This
+    /// is returned instead of [`Error::UnknownArgument`] only if the
+    /// `suggestions` feature is enabled and the command (or its parent) has
+    /// the `#[argp(suggestions)]` attribute.
+    UnknownArgumentSuggestion {
+        /// The unknown argument.
+        arg: OsString,
+        /// The most similar option or subcommand.
+        suggestion: &'static str,
+    },
+
     /// Any other error.
     Other(String),
 }
@@ -52,8 +139,31 @@ impl fmt::Display for Error {
         use Error::*;
 
         match &self {
+            AmbiguousOption { arg, candidates } => {
+                write!(f, "Option '{}' is ambiguous; possibilities:", arg)?;
+                for candidate in candidates {
+                    write!(f, " '{}'", candidate)?;
+                }
+                f.write_str(".")
+            }
+            Config {
+                source,
+                line: Some(line),
+                msg,
+            } => write!(f, "Error parsing configuration '{}' on line {}: {}.", source, line, msg),
+            Config {
+                source,
+                line: None,
+                msg,
+            } => write!(f, "Error reading configuration '{}': {}.", source, msg),
+            ConflictingOptions { arg, other } => {
+                write!(f, "Option '{}' cannot be used together with '{}'.", arg, other)
+            }
             DuplicateOption(arg) => write!(f, "Option '{}' can only be used once.", arg),
             MissingArgValue(arg) => write!(f, "Option '{}' requires a value.", arg),
+            MissingArgValues { arg, expected } => {
+                write!(f, "Option '{}' requires {} values.", arg, expected)
+            }
             MissingRequirements(req) => req.fmt(f),
             OptionsAfterHelp => {
                 write!(f, "Trailing options are not allowed after 'help' subcommand.")
@@ -66,7 +176,32 @@ impl fmt::Display for Error {
                 };
                 write!(f, "Error parsing {} '{}' with value '{:?}': {}.", subj, arg, value, msg)
             }
+            ParseConfigValue {
+                source,
+                key,
+                value,
+                msg,
+            } => write!(
+                f,
+                "Error parsing key '{}' with value '{:?}' in {}: {}.",
+                key, value, source, msg
+            ),
+            ParseEnvVar { var, value, msg } => write!(
+                f,
+                "Error parsing environment variable '{}' with value '{:?}': {}.",
+                var, value, msg
+            ),
+            ResponseFile { path, msg } => {
+                write!(f, "Error reading response file '{}': {}.", path.display(), msg)
+            }
+            UnexpectedArgValue(arg) => write!(f, "Option '{}' doesn't allow a value.", arg),
             UnknownArgument(arg) => write!(f, "Unrecognized argument: {}", arg.to_string_lossy()),
+            UnknownArgumentSuggestion { arg, suggestion } => write!(
+                f,
+                "Unrecognized argument: {}\nDid you mean '{}'?",
+                arg.to_string_lossy(),
+                suggestion
+            ),
             Other(msg) => msg.fmt(f),
         }
     }
@@ -76,7 +211,8 @@ impl fmt::Display for Error {
 #[doc(hidden)]
 #[derive(Debug, Default, PartialEq)]
 pub struct MissingRequirements {
-    options: Vec<&'static str>,
+    options: Vec<(&'static str, Option<&'static str>)>,
+    groups: Vec<&'static [&'static str]>,
     subcommands: Option<Vec<&'static str>>,
     positional_args: Vec<&'static str>,
 }
@@ -85,7 +221,20 @@ impl MissingRequirements {
     /// Adds a missing required option.
     #[doc(hidden)]
     pub fn missing_option(&mut self, name: &'static str) {
-        self.options.push(name)
+        self.options.push((name, None))
+    }
+
+    /// Adds a missing option that is required only because of the given
+    /// condition (e.g. `required by --foo`).
+    #[doc(hidden)]
+    pub fn missing_option_if(&mut self, name: &'static str, condition: &'static str) {
+        self.options.push((name, Some(condition)))
+    }
+
+    /// Adds a required group of options of which none was provided.
+    #[doc(hidden)]
+    pub fn missing_group(&mut self, options: &'static [&'static str]) {
+        self.groups.push(options)
     }
 
     /// Adds a missing required subcommand.
@@ -104,7 +253,10 @@ impl MissingRequirements {
     /// string describing the missing args.
     #[doc(hidden)]
     pub fn err_on_any(self) -> Result<(), Error> {
-        if self.options.is_empty() && self.subcommands.is_none() && self.positional_args.is_empty()
+        if self.options.is_empty()
+            && self.groups.is_empty()
+            && self.subcommands.is_none()
+            && self.positional_args.is_empty()
         {
             Ok(())
         } else {
@@ -130,14 +282,31 @@ impl fmt::Display for MissingRequirements {
                 f.write_char('\n')?;
             }
             f.write_str("Required options not provided:")?;
-            for option in &self.options {
+            for (option, condition) in &self.options {
+                f.write_str(NEWLINE_INDENT)?;
+                f.write_str(option)?;
+                if let Some(condition) = condition {
+                    write!(f, " ({})", condition)?;
+                }
+            }
+        }
+
+        for (i, group) in self.groups.iter().enumerate() {
+            if i > 0 || !self.positional_args.is_empty() || !self.options.is_empty() {
+                f.write_char('\n')?;
+            }
+            f.write_str("One of the following options must be provided:")?;
+            for option in group.iter() {
                 f.write_str(NEWLINE_INDENT)?;
                 f.write_str(option)?;
             }
         }
 
         if let Some(missing_subcommands) = &self.subcommands {
-            if !self.positional_args.is_empty() || !self.options.is_empty() {
+            if !self.positional_args.is_empty()
+                || !self.options.is_empty()
+                || !self.groups.is_empty()
+            {
                 f.write_char('\n')?;
             }
             f.write_str("One of the following subcommands must be present:")?;
@@ -8,9 +8,10 @@
 
 #![allow(missing_docs)]
 
+use std::borrow::Cow;
 use std::fmt;
 use std::iter;
-use std::ops::{Deref, Range};
+use std::ops::Range;
 use std::ptr;
 
 use crate::term_size;
@@ -18,11 +19,20 @@ use crate::term_size;
 const INDENT: &str = "  ";
 const SECTION_SEPARATOR: &str = "\n";
 
-const HELP_OPT: OptionArgInfo = OptionArgInfo {
-    usage: "",
-    description: ("-h, --help", "Show this help message and exit."),
-    global: true,
-};
+/// Returns the help option to be printed in the **Options** section.
+///
+/// - `flags`: The short option (if enabled) and the long option, e.g. `-h,
+///   --help` or `    --help`.
+pub const fn help_opt(flags: &'static str) -> OptionArgInfo {
+    OptionArgInfo {
+        usage: "",
+        description: (flags, "Show this help message and exit."),
+        global: true,
+        env: None,
+        possible_values: &[],
+        aliases: &[],
+    }
+}
 
 /// Help message generator.
 #[derive(Debug)]
@@ -41,6 +51,31 @@ pub struct HelpInfo {
     pub options: &'static [OptionArgInfo],
     pub commands: Option<CommandsHelpInfo>,
     pub footer: &'static str,
+    pub flattened: &'static [FlattenedHelpInfo],
+    /// A function that returns the version of the command, if it supports the
+    /// `-V, --version` option.
+    pub version: Option<fn() -> String>,
+    /// The short help option (e.g. `-h`), if enabled.
+    pub help_short: Option<&'static str>,
+    /// The keyword of the help subcommand (e.g. `help`), if enabled.
+    pub help_command: Option<&'static str>,
+    /// The help option to be printed in the **Options** section, reflecting
+    /// `help_short` (see [`help_opt`]).
+    pub help_option: OptionArgInfo,
+}
+
+/// A nested struct in [`HelpInfo`] with the information about a flattened
+/// struct (a field with `#[argp(flatten)]`).
+#[derive(Debug)]
+pub struct FlattenedHelpInfo {
+    /// The number of options of the parent struct preceding the options of
+    /// the flattened struct in the help message.
+    pub options_index: usize,
+    /// The number of positional arguments of the parent struct preceding the
+    /// positional arguments of the flattened struct in the help message.
+    pub positionals_index: usize,
+    /// The help information of the flattened struct.
+    pub info: &'static HelpInfo,
 }
 
 /// A nested struct in [`HelpInfo`] used for generating the Commands section in
@@ -64,6 +99,41 @@ pub struct CommandInfo {
     pub name: &'static str,
     /// A short description of the command's functionality.
     pub description: &'static str,
+    /// Alternative names of the command shown in the help message.
+    pub aliases: &'static [&'static str],
+    /// Alternative names of the command not shown in the help message.
+    pub hidden_aliases: &'static [&'static str],
+}
+
+impl CommandInfo {
+    /// Creates a command without aliases. Use the struct update syntax to
+    /// specify them, e.g. `CommandInfo { aliases: &["rm"], ..CommandInfo::new("remove", "...") }`.
+    pub const fn new(name: &'static str, description: &'static str) -> Self {
+        Self {
+            name,
+            description,
+            aliases: &[],
+            hidden_aliases: &[],
+        }
+    }
+
+    /// Returns `true` if the given name is the name or one of the aliases of
+    /// this command.
+    pub fn matches(&self, name: &str) -> bool {
+        self.name == name || self.aliases.contains(&name) || self.hidden_aliases.contains(&name)
+    }
+
+    /// Returns the name of the command followed by its visible aliases (e.g.
+    /// `remove, rm`) to be printed in the **Commands** section.
+    fn names(&self) -> Cow<'static, str> {
+        if self.aliases.is_empty() {
+            Cow::from(self.name)
+        } else {
+            let mut names = vec![self.name];
+            names.extend(self.aliases);
+            Cow::from(names.join(", "))
+        }
+    }
 }
 
 /// Information about a specific option or positional argument used for
@@ -87,6 +157,50 @@ pub struct OptionArgInfo {
     /// Whether to propagate this option down to subcommands. This is valid only
     /// for options and switches, not for positional arguments.
     pub global: bool,
+
+    /// The name of the environment variable that the option falls back to, if
+    /// any. This is valid only for options and switches.
+    pub env: Option<&'static str>,
+
+    /// The values accepted by the option or positional argument if it accepts
+    /// only a fixed set of values, otherwise empty.
+    pub possible_values: &'static [&'static str],
+
+    /// The aliases of the option to be shown in the help message (e.g.
+    /// `--colour`). This is valid only for options and switches.
+    pub aliases: &'static [&'static str],
+}
+
+impl OptionArgInfo {
+    /// Returns the description to be printed in the right column, including
+    /// the name of the environment variable, if any (e.g. `[env: FOO]`).
+    fn description_text(&self) -> Cow<'static, str> {
+        let mut desc = Cow::from(self.description.1);
+        let mut append = |text: String| {
+            let desc = desc.to_mut();
+            if !desc.is_empty() {
+                desc.push(' ');
+            }
+            desc.push_str(&text);
+        };
+
+        if !self.possible_values.is_empty() {
+            append(format!("[possible values: {}]", self.possible_values.join(", ")));
+        }
+        if !self.aliases.is_empty() {
+            append(format!("[aliases: {}]", self.aliases.join(", ")));
+        }
+        if let Some(env) = self.env {
+            append(format!("[env: {}]", env));
+        }
+        desc
+    }
+
+    /// Returns both columns of the description to be printed in the
+    /// **Options** and **Arguments** section.
+    fn description_columns(&self) -> (&'static str, Cow<'static, str>) {
+        (self.description.0, self.description_text())
+    }
 }
 
 /// Style preferences for the Help message generator.
@@ -175,7 +289,8 @@ impl Help {
         let info = self.info;
 
         let options = self.options();
-        let options_and_args = options.clone().chain(info.positionals);
+        let positionals = info.all_positionals();
+        let options_and_args = options.iter().chain(&positionals).copied();
         let subcommands = self.subcommands();
 
         let wrap_width = style.wrap_width();
@@ -187,12 +302,13 @@ impl Help {
             let left_column = options_and_args
                 .clone()
                 .map(|r| r.description.0)
-                .chain(subcommands.iter().map(|r| r.name));
+                .map(Cow::from)
+                .chain(subcommands.iter().map(|r| r.names()));
 
             // Calculates the maximum width of the content of the left column
             // that is below the max_indent threshold.
             left_column
-                .map(|s| INDENT.len() + chars_count(s) + 2)
+                .map(|s| INDENT.len() + chars_count(&s) + 2)
                 .filter(|width| *width <= max_indent)
                 .max()
                 .unwrap_or(8)
@@ -215,7 +331,7 @@ impl Help {
             w.write_usage(
                 "Usage:",
                 iter::once("[options]")
-                    .chain(info.positionals.iter().map(|r| r.usage))
+                    .chain(positionals.iter().map(|r| r.usage))
                     .chain(subcommands_usage),
             );
         } else {
@@ -224,17 +340,17 @@ impl Help {
 
         w.write_paragraphs(info.description);
 
-        if !info.positionals.is_empty() {
-            w.write_section("Arguments:", info.positionals.iter().map(|r| r.description));
+        if !positionals.is_empty() {
+            w.write_section("Arguments:", positionals.iter().map(|r| r.description_columns()));
         }
-        w.write_section("Options:", options.map(|r| r.description));
+        w.write_section("Options:", options.iter().map(|r| r.description_columns()));
 
         if !subcommands.is_empty() {
             w.write_section(
                 "Commands:",
                 subcommands
                     .iter()
-                    .map(|r| (r.name, first_line(r.description))),
+                    .map(|r| (r.names(), first_line(r.description))),
             );
         }
         if !info.footer.is_empty() {
@@ -244,14 +360,13 @@ impl Help {
         w.into_string()
     }
 
-    /// Returns global options, local options and the help option chained
-    /// together.
-    fn options(&self) -> impl Iterator<Item = &OptionArgInfo> + Clone {
-        self.global_options
-            .iter()
-            .map(Deref::deref)
-            .chain(self.info.options)
-            .chain(iter::once(&HELP_OPT))
+    /// Returns global options, local options (including the ones of the
+    /// flattened structs) and the help option chained together.
+    fn options(&self) -> Vec<&'static OptionArgInfo> {
+        let mut options = self.global_options.clone();
+        options.extend(self.info.all_options());
+        options.push(&self.info.help_option);
+        options
     }
 
     /// Returns static and dynamic subcommands chained together, or an empty
@@ -292,6 +407,34 @@ impl PartialEq for Help {
 }
 
 impl HelpInfo {
+    /// Returns the options including the ones of the flattened structs,
+    /// recursively.
+    pub fn all_options(&'static self) -> Vec<&'static OptionArgInfo> {
+        self.merge_flattened(|info| info.options, |flat| flat.options_index)
+    }
+
+    /// Returns the positional arguments including the ones of the flattened
+    /// structs, recursively.
+    pub fn all_positionals(&'static self) -> Vec<&'static OptionArgInfo> {
+        self.merge_flattened(|info| info.positionals, |flat| flat.positionals_index)
+    }
+
+    fn merge_flattened(
+        &'static self,
+        items: fn(&'static HelpInfo) -> &'static [OptionArgInfo],
+        index: fn(&FlattenedHelpInfo) -> usize,
+    ) -> Vec<&'static OptionArgInfo> {
+        let mut out: Vec<_> = items(self).iter().collect();
+
+        // The indexes refer to the own items, so the flattened items must be
+        // inserted from the last one.
+        for flat in self.flattened.iter().rev() {
+            let at = index(flat);
+            out.splice(at..at, flat.info.merge_flattened(items, index));
+        }
+        out
+    }
+
     /// Creates a new [`Help`] generator instance.
     ///
     /// - `command_name`: The identifier for the current command.
@@ -342,11 +485,15 @@ impl<'a> HelpWriter<'_> {
         }
     }
 
-    fn write_section(&mut self, title: &str, descs: impl Iterator<Item = (&'a str, &'a str)>) {
+    fn write_section<L: AsRef<str>, R: AsRef<str>>(
+        &mut self,
+        title: &str,
+        descs: impl Iterator<Item = (L, R)>,
+    ) {
         // NOTE: greedy positional has empty names and description, to be
         // excluded from the Positional Arguments section.
         let mut first = true;
-        for desc in descs.filter(|desc| !desc.0.is_empty()) {
+        for desc in descs.filter(|desc| !desc.0.as_ref().is_empty()) {
             if first {
                 self.write_str(SECTION_SEPARATOR);
                 self.write_line(title);
@@ -354,7 +501,7 @@ impl<'a> HelpWriter<'_> {
             } else {
                 self.write_str(self.blank_lines_spacing);
             }
-            self.write_description(desc);
+            self.write_description((desc.0.as_ref(), desc.1.as_ref()));
         }
     }
 
@@ -51,15 +51,113 @@
 //! - `./some_bin --height 5`
 //! - `./some_bin -j --height 5`
 //! - `./some_bin --jump --height 5 --pilot-nickname Wes`
+//! - `./some_bin --height=5 --pilot-nickname=Wes`
 //!
 //! Switches, like `jump`, are optional and will be set to true if provided.
 //!
+//! A switch marked with `#[argp(switch, negatable)]` also accepts the negative
+//! form `--no-<name>`, which sets it back to `false` (or `Some(false)` for
+//! `Option<bool>`). The last occurrence wins.
+//!
 //! Options, like `height` and `pilot_nickname`, can be either required,
 //! optional, or repeating, depending on whether they are contained in an
 //! `Option` or a `Vec`. Default values can be provided using the
 //! `#[argp(default = "<your_code_here>")]` attribute, and in this case an
 //! option is treated as optional.
 //!
+//! Repeating options can also accept multiple values in a single occurrence
+//! separated by a delimiter specified with the `#[argp(delimiter = ',')]`
+//! attribute (e.g. `--tag a,b,c`). The delimiter can be escaped with a
+//! backslash.
+//!
+//! Options of a tuple or an array type (e.g. `(String, String)` or
+//! `[f64; 3]`, also wrapped in `Option` or `Vec`) take a fixed number of values
+//! per occurrence (e.g. `--rename <old> <new>`). The values can be named in the
+//! help message using `#[argp(arg_name = "old new")]`.
+//!
+//! An option with the `#[argp(default_missing = "<value>")]` attribute can be
+//! also provided without a value (e.g. `--color` vs `--color=always`), in which
+//! case it gets the specified value. Its value can then only be attached to it
+//! with `=`, the next argument is never consumed.
+//!
+//! A non-repeating option can be provided only once by default. With the
+//! `#[argp(overridable)]` attribute on the field, or on the struct for all its
+//! options and switches, it can be provided more than once and the last
+//! occurrence overrides the previous ones (e.g. `--format=json --format=table`
+//! yields `table`). This also applies to global options provided in
+//! subcommands.
+//!
+//! Options and switches can have alternative names specified using the
+//! `#[argp(alias = "--colour")]` or `#[argp(aliases = ["--colour", "-C"])]`
+//! attribute, and subcommands using `#[argp(alias = "rm")]` on the subcommand
+//! struct. The aliases are shown in the help message, unless specified using
+//! `#[argp(hidden_alias = "...")]`.
+//!
+//! Options and positional arguments shared by multiple commands can be
+//! declared in a separate struct deriving `FromArgs` and embedded into a
+//! command using a field with the `#[argp(flatten)]` attribute. The flattened
+//! options behave as if they were declared in the command itself (including
+//! `global`) and are listed together at the position of the field in the help
+//! message. The option names must be unique across the command and all its
+//! flattened structs, and flattened structs cannot have subcommands. A struct
+//! with `global` options must contain a `#[argp(subcommand)]` field, or have
+//! the `#[argp(flatten)]` attribute on the struct and be flattened only into
+//! structs that do. Violations are reported at compile time (or by a debug
+//! assertion for generic structs).
+//!
+//! Options and switches that cannot be used together can be declared using the
+//! `#[argp(conflicts_with = "<field>")]` attribute (it can be repeated). Groups
+//! of options and switches are declared on the struct using
+//! `#[argp(group(name = "<name>", required, multiple = false))]` and the
+//! members are assigned using `#[argp(group = "<name>")]`. At least one member
+//! of a `required` group must be provided, and at most one unless the group
+//! allows `multiple = true` (the default is `false`). Values from environment
+//! variables and the configuration count as provided too. The group is shown
+//! in the usage as `(--json | --yaml | --table)`.
+//!
+//! Options can be also required conditionally: `#[argp(requires = "<field>")]`
+//! makes the other option or switch required if this one is provided,
+//! `#[argp(required_unless = "<field>")]` makes this option required unless
+//! any of the specified ones is provided, and
+//! `#[argp(required_if = "<field>=<value>")]` makes it required if the other
+//! option has the given value (or if it's provided at all when `=<value>` is
+//! omitted). The value is parsed the same way as on the command line and
+//! compared with `PartialEq`, the default value doesn't count. If the other
+//! option has possible values (e.g. a derived [`FromArgValue`] enum), the
+//! value must be one of them, this is checked at compile time. All of these
+//! attributes can be repeated.
+//!
+//! The values of options and positional arguments can be restricted to a range
+//! using the `#[argp(range = "1..=256")]` attribute, or to a list of strings
+//! using `#[argp(possible_values = ["debug", "info"])]`. The constraint is
+//! shown in the help message and a value that doesn't satisfy it is reported
+//! as an error parsing the option.
+//!
+//! ```rust
+//! use argp::FromArgs;
+//!
+//! /// List packages.
+//! #[derive(FromArgs)]
+//! #[argp(group(name = "format", required))]
+//! struct List {
+//!     /// Print as JSON.
+//!     #[argp(switch, group = "format")]
+//!     json: bool,
+//!
+//!     /// Print as YAML.
+//!     #[argp(switch, group = "format")]
+//!     yaml: bool,
+//!
+//!     /// List all packages.
+//!     #[argp(switch, conflicts_with = "package")]
+//!     all: bool,
+//!
+//!     /// Package to list.
+//!     #[argp(option)]
+//!     package: Vec<String>,
+//! }
+//! ```
+//!
 //! ```rust
 //! use argp::FromArgs;
 //!
@@ -88,12 +186,29 @@
 //! }
 //! ```
 //!
+//! Options and switches can fall back to an environment variable when they're
+//! not provided on the command line, using the `#[argp(env = "APP_PORT")]`
+//! attribute. The value is parsed the same way as the command-line value and
+//! takes precedence over `default`. Switches accept `1`, `true`, `yes`, `on`
+//! or `0`, `false`, `no`, `off`. The `#[argp(env_prefix = "APP_")]`
+//! attribute on the struct derives the variable name from the long name of
+//! each option and switch (e.g. `APP_PILOT_NICKNAME` for `--pilot-nickname`).
+//!
+//! Values can also be taken from a configuration file (or any other
+//! [`ConfigSource`]) with keys mirroring the long option names, using
+//! [`FromArgs::from_args_with_config`]. The precedence is: command line,
+//! environment variable, configuration, `default`. A fallback value is ignored
+//! if the option conflicts with one provided with a higher precedence (see
+//! `conflicts_with` and groups).
+//!
 //! Custom option types can be deserialized so long as they implement the
 //! [`FromArgValue`] trait (already implemented for most types in std for which
-//! the `FromStr` trait is implemented). If more customized parsing is required,
-//! you can supply a custom `fn(&str) -> Result<T, E>` using the `from_str_fn`
-//! attribute, or `fn(&OsStr) -> Result<T, E>` using the `from_os_str_fn`
-//! attribute, where `E` implements `ToString`:
+//! the `FromStr` trait is implemented). Fieldless enums and newtypes can derive
+//! it with `#[derive(FromArgValue)]`; the values of an enum are the kebab-case
+//! variant names and are listed in the help message. If more customized parsing
+//! is required, you can supply a custom `fn(&str) -> Result<T, E>` using the
+//! `from_str_fn` attribute, or `fn(&OsStr) -> Result<T, E>` using the
+//! `from_os_str_fn` attribute, where `E` implements `ToString`:
 //!
 //! ```
 //! # use argp::FromArgs;
@@ -138,6 +253,11 @@
 //! The last positional argument may include a default, or be wrapped in
 //! `Option` or `Vec` to indicate an optional or repeating positional argument.
 //!
+//! One `Vec` positional argument may also be followed by required positional
+//! arguments (e.g. `<source...> <dest>` like in `cp`). The trailing arguments
+//! take the last values and the rest is collected into the `Vec`, which must
+//! receive at least one value in this case.
+//!
 //! If your final positional argument has the `greedy` option on it, it will
 //! consume any arguments after it as if a `--` were placed before the first
 //! argument to match the greedy positional:
@@ -167,6 +287,46 @@
 //! the rest of the arguments can be interpreted, and shouldn't be used for
 //! regular use as it might be confusing.
 //!
+//! Arguments starting with `-` are parsed as options, except negative numbers
+//! (e.g. `-5` or `-0.5`) which are parsed as positional arguments if the command
+//! has no numeric short options (e.g. `-1`). Values of options are always taken
+//! as is, so `--offset -5` works too. If a positional argument has the
+//! `allow_hyphen_values` option on it, any argument starting with `-` that isn't
+//! a known option is parsed as its value (e.g. `-x` or `-` for stdin).
+//!
+//! Options and positional arguments can be freely interleaved. If you add the
+//! `#[argp(options_first)]` attribute to a command, or if the `POSIXLY_CORRECT`
+//! environment variable is set, parsing of options stops at the first
+//! positional argument, as with `getopt`. This is useful for wrapper tools, so
+//! that e.g. in `runner -v cmd -v`, the second `-v` is left to `cmd`.
+//!
+//! If you add the `#[argp(version)]` attribute to a command, it gets the
+//! `-V, --version` option that exits early with [`EarlyExit::Version`], like
+//! `--help` does, so required arguments don't have to be provided. The version
+//! defaults to the version of your crate, or you can specify it as
+//! `#[argp(version = "1.2.3")]`, or as a path to a function returning a string
+//! (`#[argp(version = path::to::fn)]`). [`parse_args_or_exit`] prints it to
+//! stdout (after the command name) and exits with code 0.
+//!
+//! The help message is requested by `--help`, `-h` or the `help` subcommand
+//! keyword (e.g. `cmd help` or `cmd help <subcommand>`). If you need `-h` for
+//! something else (e.g. `--human-readable`), or the literal word `help` as
+//! a positional argument, you can rename them with the `help_short = 'H'` and
+//! `help_command = "usage"` attributes on the command, or disable them with
+//! `help_short = false` and `help_command = false`.
+//!
+//! Like with `getopt_long`, long options can be abbreviated to any unambiguous
+//! prefix (e.g. `--verb` for `--verbose`) if you add the
+//! `#[argp(abbreviations)]` attribute to the top-level command. This setting is
+//! inherited by all its subcommands, so it cannot be used on a subcommand.
+//!
+//! Arguments can also be passed through response files, as in gcc or rustc,
+//! if you add the `#[argp(response_files)]` attribute to the top-level command.
+//! Each `@path` argument is then replaced with the (whitespace-separated,
+//! possibly quoted) arguments from the file at `path`. The arguments of
+//! subcommands are then already expanded, so this attribute cannot be used on
+//! a subcommand. See [`expand_response_files`] for details.
+//!
 //! ## Subcommands
 //!
 //! Subcommands are also supported. To use a subcommand, declare a separate
@@ -223,6 +383,36 @@
 //! level to be used at a lower level (in subcommands), you can specify the
 //! `global` attribute to the option (`--verbose` in the example above).
 //!
+//! Small subcommands can be also declared inline as enum variants with named
+//! fields, or as unit variants for subcommands without arguments. The name of
+//! the subcommand defaults to the kebab-case'd variant name (e.g. `DryRun` to
+//! `dry-run`), the doc comment of the variant is used as the description, and
+//! the variant can have the same attributes as a subcommand struct:
+//!
+//! ```rust
+//! # use argp::FromArgs;
+//! #[derive(FromArgs, PartialEq, Debug)]
+//! #[argp(subcommand)]
+//! enum Command {
+//!     /// Add a package.
+//!     Add {
+//!         /// Name of the package.
+//!         #[argp(positional)]
+//!         name: String,
+//!     },
+//!     /// List packages.
+//!     #[argp(alias = "ls")]
+//!     List,
+//! }
+//! ```
+//!
+//! Both commands and subcommand enums can be generic, e.g. a shared front-end
+//! `struct Cli<S>` with a `#[argp(subcommand)] cmd: S` field can be used with
+//! different subcommand enums. The bounds required by the fields
+//! ([`SubCommands`] for a subcommand, [`FromArgValue`] for an option or
+//! a positional argument, [`SubCommand`] for a variant of a subcommand enum)
+//! are added to the generated implementations automatically.
+//!
 //! Global options only propagate down, not up (to parent commands), but their
 //! values are propagated back up to the parent once a user has used them. In
 //! effect, this means that you should define all global arguments at the top
@@ -285,10 +475,10 @@
 //!             // reference, but only because this is a synthetic example; the
 //!             // point of using dynamic commands is to have commands you
 //!             // don't know about until runtime!
-//!             commands.push(&*Box::leak(Box::new(CommandInfo {
-//!                 name: "dynamic_command",
-//!                 description: "A dynamic command",
-//!             })));
+//!             commands.push(&*Box::leak(Box::new(CommandInfo::new(
+//!                 "dynamic_command",
+//!                 "A dynamic command",
+//!             ))));
 //!
 //!             commands
 //!         })
@@ -308,6 +498,18 @@
 //! }
 //! ```
 //!
+//! ## Suggestions
+//!
+//! If the `suggestions` feature is enabled and you add the
+//! `#[argp(suggestions)]` attribute to the top-level command, an unknown long
+//! option or subcommand that is similar to a known one (e.g. `--hieght` or
+//! `sttaus`) is reported as [`Error::UnknownArgumentSuggestion`] instead of
+//! [`Error::UnknownArgument`], and the error message ends with
+//! `Did you mean '--height'?`. This setting is inherited by all its
+//! subcommands, so it cannot be used on a subcommand. The feature is disabled by default to keep the code size
+//! small, and the attribute has no effect without it, so enabling the feature
+//! doesn't change the errors of commands that don't opt in.
+//!
 //! ## Help message
 //!
 //! The formatting of the help message can be customized using the [`HelpStyle`]
@@ -391,9 +593,13 @@
 
 #![deny(missing_docs)]
 
+pub mod config;
 mod error;
 pub mod help;
 pub mod parser;
+mod response_file;
+#[cfg(feature = "suggestions")]
+mod suggestions;
 pub mod term_size;
 
 use std::borrow::Cow;
@@ -403,12 +609,14 @@ use std::fmt;
 use std::path::{Path, PathBuf};
 use std::process::exit;
 
+use crate::config::ConfigSource;
 use crate::help::{Help, HelpInfo};
-use crate::parser::ParseGlobalOptions;
+use crate::parser::{ParseConfig, ParseGlobalOptions};
 
 pub use crate::error::{Error, MissingRequirements};
 pub use crate::help::{CommandInfo, HelpStyle};
-pub use argp_derive::FromArgs;
+pub use crate::response_file::expand_response_files;
+pub use argp_derive::{FromArgValue, FromArgs};
 
 /// A convenient shortcut for [`HelpStyle::default`].
 pub const DEFAULT: &HelpStyle = &HelpStyle::default();
@@ -539,6 +747,53 @@ pub trait FromArgs: Sized {
         Self::_from_args(command_name, &args, None)
     }
 
+    /// Construct the type from an input set of arguments, falling back to the
+    /// given configuration source for options and switches that haven't been
+    /// provided on the command line (nor in their environment variables).
+    ///
+    /// The precedence is: command line, environment variable, `config`,
+    /// `default`. Required options are reported as missing only if they're
+    /// not found in any of these.
+    ///
+    /// See [`FromArgs::from_args`] for the description of the arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use argp::FromArgs;
+    /// use argp::config::KeyValueConfig;
+    ///
+    /// /// Reach new heights.
+    /// #[derive(Debug, PartialEq, FromArgs)]
+    /// struct GoUp {
+    ///     /// How high to go.
+    ///     #[argp(option)]
+    ///     height: usize,
+    ///
+    ///     /// An optional nickname for the pilot.
+    ///     #[argp(option)]
+    ///     pilot_nickname: Option<String>,
+    /// }
+    ///
+    /// let config = KeyValueConfig::parse("goup.conf", "height = 5\npilot-nickname = Wes").unwrap();
+    /// let up = GoUp::from_args_with_config(&["goup"], &["--height", "8"], &config).unwrap();
+    /// assert_eq!(
+    ///     up,
+    ///     GoUp {
+    ///         height: 8,
+    ///         pilot_nickname: Some("Wes".to_owned()),
+    ///     },
+    /// );
+    /// ```
+    fn from_args_with_config<S: AsRef<OsStr>>(
+        command_name: &[&str],
+        args: &[S],
+        config: &dyn ConfigSource,
+    ) -> Result<Self, EarlyExit> {
+        let args: Vec<_> = args.iter().map(AsRef::as_ref).collect();
+        Self::_from_args(command_name, &args, Some(&mut ParseConfig(config)))
+    }
+
     #[doc(hidden)]
     fn _from_args(
         command_name: &[&str],
@@ -606,8 +861,38 @@ pub trait CommandHelp: FromArgs {
 /// * [`std::net::IpAddr`] and its variants
 /// * [`std::net::SocketAddr`] and its variants
 ///
-/// Custom types should implement this trait directly.
+/// Custom types should implement this trait directly, or derive it using
+/// `#[derive(FromArgValue)]` if they are fieldless enums or newtypes:
+///
+/// ```
+/// use argp::FromArgValue;
+///
+/// #[derive(FromArgValue)]
+/// #[argp(case_insensitive)]
+/// enum Level {
+///     Debug,
+///     #[argp(name = "info", alias = "information")]
+///     Informational,
+/// }
+///
+/// #[derive(FromArgValue)]
+/// struct Jobs(u16);
+///
+/// assert_eq!(Level::POSSIBLE_VALUES, ["debug", "info"]);
+/// assert!(matches!(Level::from_arg_value("INFO".as_ref()), Ok(Level::Informational)));
+/// assert_eq!(Jobs::from_arg_value("4".as_ref()).map(|j| j.0), Ok(4));
+/// ```
+///
+/// The enum variants are matched by their names in kebab-case (e.g. `DryRun`
+/// as `dry-run`), unless renamed using `#[argp(name = "...")]`, and by their
+/// aliases (`#[argp(alias = "...")]`, can be repeated). With
+/// `#[argp(case_insensitive)]` on the enum, the ASCII case is ignored. A
+/// newtype delegates to the type of its single field.
 pub trait FromArgValue: Sized {
+    /// The values accepted by this type if it accepts only a fixed set of
+    /// values (e.g. an enum), otherwise empty. It's shown in the help message.
+    const POSSIBLE_VALUES: &'static [&'static str] = &[];
+
     /// Construct the type from a command-line value, returning an error string
     /// on failure.
     fn from_arg_value(value: &OsStr) -> Result<Self, String>;
@@ -671,7 +956,8 @@ impl_from_arg_value_from_str![
 /// Information to display to the user about why a [`FromArgs`] construction
 /// exited early.
 ///
-/// This can occur due to either failed parsing or a flag like `--help`.
+/// This can occur due to either failed parsing or a flag like `--help` or
+/// `--version`.
 #[derive(Debug, PartialEq)]
 pub enum EarlyExit {
     /// Early exit and display the error message.
@@ -679,6 +965,9 @@ pub enum EarlyExit {
 
     /// Early exit and display the help message.
     Help(Help),
+
+    /// Early exit and display the version (see `#[argp(version)]`).
+    Version(String),
 }
 
 impl fmt::Display for EarlyExit {
@@ -686,6 +975,7 @@ impl fmt::Display for EarlyExit {
         match self {
             EarlyExit::Err(err) => err.fmt(f),
             EarlyExit::Help(help) => help.fmt(f),
+            EarlyExit::Version(version) => version.fmt(f),
         }
     }
 }
@@ -702,7 +992,8 @@ impl From<Error> for EarlyExit {
 ///
 /// This function will exit early from the current process if argument parsing
 /// was unsuccessful or if information like `--help` was requested. Error
-/// messages will be printed to stderr, and `--help` output to stdout.
+/// messages will be printed to stderr, and `--help` and `--version` output
+/// (the command name followed by the version) to stdout.
 pub fn parse_args_or_exit<T: TopLevelCommand>(help_style: &HelpStyle) -> T {
     let args: Vec<_> = env::args_os().collect();
     if args.is_empty() {
@@ -717,6 +1008,10 @@ pub fn parse_args_or_exit<T: TopLevelCommand>(help_style: &HelpStyle) -> T {
                 println!("{}", help.generate(help_style));
                 0
             }
+            EarlyExit::Version(version) => {
+                println!("{} {}", cmd, version);
+                0
+            }
             EarlyExit::Err(err) => {
                 eprintln!("{}\nRun {} --help for more information.", err, cmd);
                 1
@@ -738,7 +1033,8 @@ pub fn from_env<T: TopLevelCommand>() -> T {
 ///
 /// This function will exit early from the current process if argument parsing
 /// was unsuccessful or if information like `--help` was requested. Error
-/// messages will be printed to stderr, and `--help` output to stdout.
+/// messages will be printed to stderr, and `--help` and `--version` output
+/// (the command name followed by the version) to stdout.
 pub fn cargo_parse_args_or_exit<T: TopLevelCommand>() -> T {
     let args: Vec<_> = env::args_os().collect();
     let cmd = basename(&args[1]);
@@ -749,6 +1045,10 @@ pub fn cargo_parse_args_or_exit<T: TopLevelCommand>() -> T {
                 println!("{}", help.generate_default());
                 0
             }
+            EarlyExit::Version(version) => {
+                println!("{} {}", cmd, version);
+                0
+            }
             EarlyExit::Err(err) => {
                 eprintln!("{}\nRun --help for more information.", err);
                 1
@@ -7,6 +7,7 @@
 
 use std::ffi::{OsStr, OsString};
 
+use crate::config::ConfigSource;
 use crate::error::Error;
 use crate::help::{CommandInfo, HelpInfo, OptionArgInfo};
 use crate::EarlyExit;
@@ -15,19 +16,74 @@ use crate::EarlyExit;
 ///
 /// - `cmd_name`: The identifier for the current command.
 /// - `args`: The command line arguments.
-/// - `parse_options`: Helper to parse optional arguments.
-/// - `parse_positionals`: Helper to parse positional arguments.
-/// - `parse_subcommand`: Helper to parse a subcommand.
+/// - `fragment`: The parsing tables of the struct, including the ones of its
+///   flattened structs.
 /// - `help`: The [`Help`] instance for generating a help message.
+/// - `response_files`: The function expanding response files (`@file`
+///   arguments) before parsing ([`crate::expand_response_files`]), or `None` if
+///   response files are not enabled.
+/// - `abbreviations`: See [`ParseStructOptions::abbreviations`].
+/// - `options_first`: Whether to stop parsing options at the first positional
+///   argument, so that the remaining arguments are parsed as positional ones
+///   (e.g. `runner -v cmd -v`). This is also enabled if the `POSIXLY_CORRECT`
+///   environment variable is set.
+/// - `suggestions`: See [`ParseStructOptions::suggestions`].
+/// - `parent`: See [`ParseStructOptions::parent`].
 #[doc(hidden)]
+#[allow(clippy::too_many_arguments)]
 pub fn parse_struct_args(
     cmd_name: &[&str],
     args: &[&OsStr],
-    mut parse_options: ParseStructOptions<'_, '_>,
-    mut parse_positionals: ParseStructPositionals<'_>,
-    mut parse_subcommand: Option<ParseStructSubCommand<'_>>,
+    fragment: ParseStructFragment<'_>,
     help: &'static HelpInfo,
+    response_files: Option<ExpandResponseFiles>,
+    abbreviations: bool,
+    options_first: bool,
+    suggestions: bool,
+    parent: Option<&mut dyn ParseGlobalOptions>,
 ) -> Result<(), EarlyExit> {
+    // This is checked at compile time, except for global options of flattened
+    // structs in generic structs.
+    debug_assert!(
+        fragment.subcommand.is_some() || !fragment.slots_global.contains(&true),
+        "`global` may only be used when the struct contains a `#[argp(subcommand)]` field"
+    );
+    let options_first = options_first || std::env::var_os("POSIXLY_CORRECT").is_some();
+
+    let mut parse_options = ParseStructOptions {
+        arg_to_slot: fragment.arg_to_slot,
+        slots: fragment.slots,
+        slots_global: fragment.slots_global,
+        help,
+        slots_env: fragment.slots_env,
+        slots_default_missing: fragment.slots_default_missing,
+        slots_overridable: fragment.slots_overridable,
+        slots_conflicts: fragment.slots_conflicts,
+        slots_provided: fragment.slots_provided,
+        abbreviations,
+        suggestions,
+        parent,
+        version_requested: None,
+    };
+    let mut parse_positionals = ParseStructPositionals {
+        positionals: fragment.positionals,
+        repeating: fragment.repeating_positional,
+        last_is_greedy: fragment.last_positional_is_greedy,
+        deferred: Vec::new(),
+    };
+    let mut parse_subcommand = fragment.subcommand;
+    let fill_from_env = fragment.fill_from_env;
+
+    let expanded_args;
+    let expanded_args_refs: Vec<&OsStr>;
+    let args = if let Some(expand_response_files) = response_files {
+        expanded_args = expand_response_files(args)?;
+        expanded_args_refs = expanded_args.iter().map(OsString::as_os_str).collect();
+        &expanded_args_refs
+    } else {
+        args
+    };
+
     let mut help_requested = false;
     let mut help_cmd = false;
     let mut remaining_args = args;
@@ -38,13 +94,19 @@ pub fn parse_struct_args(
         remaining_args = &remaining_args[1..];
         let next_arg = next_arg_os.to_str().unwrap_or("");
 
-        if matches!(next_arg, "--help" | "-h" | "help") && !options_ended {
+        let is_help_cmd = help.help_command == Some(next_arg);
+        if (next_arg == "--help" || help.help_short == Some(next_arg) || is_help_cmd)
+            && !options_ended
+        {
             help_requested = true;
-            help_cmd = next_arg_os == "help";
+            help_cmd = is_help_cmd;
             continue;
         }
 
-        if next_arg.starts_with('-') && !options_ended {
+        if next_arg_os.as_encoded_bytes().starts_with(b"-")
+            && !options_ended
+            && !parse_positionals.takes_hyphen_value(positional_index, next_arg, &parse_options)
+        {
             if next_arg_os == "--" {
                 options_ended = true;
                 continue;
@@ -54,27 +116,22 @@ pub fn parse_struct_args(
                 return Err(Error::OptionsAfterHelp.into());
             }
 
-            // Handle combined short options; `-ab` is parsed as `-a -b`,
-            // `-an 5` as `-a -n 5`, but `-na 5` would fail.
-            if next_arg.len() > 2 && &next_arg[1..2] != "-" {
-                let mut chars = next_arg[1..].chars().peekable();
-
-                while let Some(short) = chars.next() {
-                    // Only the last option can accept a value.
-                    if chars.peek().is_some() {
-                        parse_options.parse(&format!("-{}", short), &mut (&[] as &[&OsStr]))?;
-                    } else {
-                        parse_options.parse(&format!("-{}", short), &mut remaining_args)?;
-                    }
-                }
+            if next_arg_os.len() > 1 && !next_arg_os.as_encoded_bytes().starts_with(b"--") {
+                parse_options.parse_short_cluster(next_arg_os, &mut remaining_args)?;
             } else {
-                parse_options.parse(next_arg, &mut remaining_args)?;
+                let (arg, value) = split_attached_value(next_arg_os);
+                parse_options.parse(arg, value, &mut remaining_args)?;
             }
 
             continue;
         }
 
         if let Some(ref mut parse_subcommand) = parse_subcommand {
+            // `--version` is not passed to the subcommand (unlike `--help`),
+            // so don't parse it at all.
+            if parse_options.version_requested.is_some() && !help_requested {
+                break 'parse_args;
+            }
             if parse_subcommand.parse(
                 help_requested,
                 cmd_name,
@@ -88,7 +145,17 @@ pub fn parse_struct_args(
             }
         }
 
-        options_ended |= parse_positionals.parse(&mut positional_index, next_arg_os)?;
+        let res = parse_positionals.parse(&mut positional_index, next_arg_os);
+        #[cfg(feature = "suggestions")]
+        let res = res.map_err(|err| match (err, &parse_subcommand) {
+            (Error::UnknownArgument(arg), Some(parse_subcommand))
+                if parse_options.suggestions() =>
+            {
+                parse_subcommand.unknown_subcommand(arg)
+            }
+            (err, _) => err,
+        });
+        options_ended |= res? || options_first;
     }
 
     if help_requested {
@@ -97,34 +164,205 @@ pub fn parse_struct_args(
             .map_or_else(Vec::new, |p| p.global_options());
 
         Err(EarlyExit::Help(help.help(cmd_name.join(" "), global_options)))
+    } else if let Some(version) = parse_options.version_requested {
+        Err(EarlyExit::Version(version()))
     } else {
+        parse_positionals.assign_deferred()?;
+        if let Some(fill_from_env) = fill_from_env {
+            fill_from_env(&mut parse_options)?;
+        }
+        if let Some(parent) = parse_options.parent.take() {
+            parent.fill_from_config(&mut parse_options)?;
+        }
         Ok(())
     }
 }
 
+/// Fills the slots of the options that haven't been provided on the command
+/// line from their environment variables, if specified and set. This is passed
+/// in [`ParseStructFragment::fill_from_env`] only if some option has an
+/// environment variable.
+///
+/// A slot is not filled if it conflicts with a slot provided with a higher
+/// precedence (command line > environment variable > configuration), so
+/// a fallback value never conflicts with an explicitly provided one.
+#[doc(hidden)]
+pub fn fill_from_env(options: &mut ParseStructOptions<'_, '_>) -> Result<(), Error> {
+    let slots_env = match options.slots_env {
+        Some(table) => table,
+        None => return Ok(()),
+    };
+    let provided_on_cli = options.slots_provided.to_vec();
+
+    for (pos, var) in slots_env.iter().enumerate() {
+        if options.slots_provided[pos] || options.conflicts_with_any(pos, &provided_on_cli) {
+            continue;
+        }
+        if let Some(var) = *var {
+            if let Some(value) = std::env::var_os(var) {
+                fill_slot_fallback(&mut options.slots[pos], var, &value).map_err(|msg| {
+                    Error::ParseEnvVar {
+                        var: var.to_owned(),
+                        value,
+                        msg,
+                    }
+                })?;
+                options.slots_provided[pos] = true;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Fills the slot with a value from an environment variable or a configuration
+/// source. Returns an error message if the value cannot be parsed.
+///
+/// - `name`: The name of the environment variable or configuration key.
+fn fill_slot_fallback(
+    slot: &mut ParseStructOption<'_>,
+    name: &str,
+    value: &OsStr,
+) -> Result<(), String> {
+    match slot {
+        ParseStructOption::Flag(ref mut b) => match parse_bool_value(value) {
+            Some(true) => b.set_flag(name),
+            Some(false) => b.unset_flag(),
+            None => return Err("expected a boolean value".to_owned()),
+        },
+        ParseStructOption::Value(ref mut pvs) => {
+            pvs.fill_slot(name, value).map_err(|err| match err {
+                Error::ParseArgument { msg, .. } => msg,
+                Error::DuplicateOption(_) => "can only be specified once".to_owned(),
+                err => err.to_string(),
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Parses a boolean value of an environment variable or a configuration key
+/// for a switch. Returns `None` if the value isn't recognised.
+fn parse_bool_value(value: &OsStr) -> Option<bool> {
+    match value.to_str()?.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "" | "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Splits a long option argument with an attached value (`--foo=bar`) into the
+/// option name and the value. If `arg` doesn't contain `=`, it's returned as
+/// is with no value.
+///
+/// The option name is expected to be valid UTF-8, the value may be any
+/// `OsStr`.
+fn split_attached_value(arg: &OsStr) -> (&str, Option<&OsStr>) {
+    let bytes = arg.as_encoded_bytes();
+
+    match bytes.iter().position(|b| *b == b'=') {
+        Some(pos) => {
+            (std::str::from_utf8(&bytes[..pos]).unwrap_or(""), Some(os_str_after(arg, pos)))
+        }
+        None => (arg.to_str().unwrap_or(""), None),
+    }
+}
+
+/// Returns `true` if the given argument is a negative number (e.g. `-5` or
+/// `-0.5`).
+fn is_negative_number(arg: &str) -> bool {
+    // This is intentionally not parsed as a float to not link the float parser
+    // into every binary.
+    let num = match arg.strip_prefix('-') {
+        Some(num) => num,
+        None => return false,
+    };
+    let (int, frac) = match num.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (num, None),
+    };
+    let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+
+    is_digits(int) && frac.map_or(true, is_digits)
+}
+
+/// Returns the part of `s` after the byte at `index`, which must be an ASCII
+/// character.
+pub(crate) fn os_str_after(s: &OsStr, index: usize) -> &OsStr {
+    let bytes = s.as_encoded_bytes();
+    assert!(bytes[index].is_ascii());
+
+    // SAFETY: `bytes` is split right after an ASCII character, so the remainder
+    // is a valid encoded `OsStr`.
+    unsafe { OsStr::from_encoded_bytes_unchecked(&bytes[index + 1..]) }
+}
+
 #[doc(hidden)]
 pub struct ParseStructOptions<'a, 'p> {
     /// A mapping from option string literals to the entry in the output table.
     /// This may contain multiple entries mapping to the same location in the
     /// table if both a short and long version of the option exist (`-z` and
     /// `--zoo`).
-    pub arg_to_slot: &'static [(&'static str, usize)],
+    pub arg_to_slot: &'a [(&'static str, usize)],
 
     /// The storage for argument output data.
     pub slots: &'a mut [ParseStructOption<'a>],
 
     /// A boolean flag for each element of the `slots` slice that specifies
     /// whether the option(s) associated with the slot is a global option.
-    pub slots_global: &'static [bool],
+    pub slots_global: &'a [bool],
 
     /// A reference to the [`Help`] struct in the associated [`FromArgs`]. This
     /// is used to collect global options for generating a help message.
     pub help: &'static HelpInfo,
 
+    /// The name of the environment variable for each element of the `slots`
+    /// slice to fall back to if the option isn't provided on the command line,
+    /// or `None` if no option has an environment variable.
+    pub slots_env: Option<&'a [Option<&'static str>]>,
+
+    /// The value for each element of the `slots` slice to be used if the option
+    /// is provided without a value, or `None` if no option has it. If
+    /// specified, the option's value can be provided only attached to it (e.g.
+    /// `--foo=bar`).
+    pub slots_default_missing: Option<&'a [Option<&'static str>]>,
+
+    /// A boolean flag for each element of the `slots` slice that specifies
+    /// whether the option may be provided more than once, with the last
+    /// occurrence overriding the previous ones, or `None` if no option is
+    /// overridable. Otherwise, providing a non-repeating option more than once
+    /// is an error.
+    pub slots_overridable: Option<&'a [bool]>,
+
+    /// Pairs of positions in the `slots` slice of the options that cannot be
+    /// used together (see `conflicts_with` and groups). These are checked by
+    /// the generated code, here they're used only to not fill a slot from its
+    /// fallback if it conflicts with an explicitly provided one.
+    pub slots_conflicts: &'a [(usize, usize)],
+
+    /// A boolean flag for each element of the `slots` slice that is set to
+    /// `true` once the option is provided on the command line, or filled from
+    /// its environment variable or the configuration. It must be initialised
+    /// with `false` values.
+    pub slots_provided: &'a mut [bool],
+
+    /// Whether to accept unambiguous prefixes of long options (e.g. `--verb`
+    /// for `--verbose`). This is inherited by subcommands.
+    pub abbreviations: bool,
+
+    /// Whether to suggest a similar long option or subcommand for an unknown
+    /// argument (see [`Error::UnknownArgumentSuggestion`]). This is inherited
+    /// by subcommands and has an effect only if the `suggestions` feature is
+    /// enabled.
+    pub suggestions: bool,
+
     /// If this struct represents options of a subcommand, then `parent` is an
     /// indirect reference to the previous [`ParseStructOptions`] in the chain,
     /// used for parsing global options.
     pub parent: Option<&'p mut dyn ParseGlobalOptions>,
+
+    /// The function returning the version (see [`HelpInfo::version`]), set
+    /// once `-V` or `--version` is provided.
+    pub version_requested: Option<fn() -> String>,
 }
 
 impl<'a> ParseStructOptions<'a, '_> {
@@ -134,34 +372,327 @@ impl<'a> ParseStructOptions<'a, '_> {
     /// `Err("Unrecognized argument: {arg}")`.
     ///
     /// - `arg`: The current option argument being parsed (e.g. `--foo`).
+    /// - `value`: The value attached to the option argument, if any (e.g.
+    ///   `bar` in `--foo=bar`).
     /// - `remaining_args`: The remaining command line arguments. This slice
-    ///   will be advanced forwards if the option takes a value argument.
-    fn parse(&mut self, arg: &str, remaining_args: &mut &[&OsStr]) -> Result<(), Error> {
+    ///   will be advanced forwards if the option takes a value argument and
+    ///   `value` is `None`.
+    fn parse(
+        &mut self,
+        arg: &str,
+        value: Option<&OsStr>,
+        remaining_args: &mut &[&OsStr],
+    ) -> Result<(), Error> {
         match self.arg_to_slot.iter().find(|(name, _)| *name == arg) {
-            Some((_, pos)) => Self::fill_slot(&mut self.slots[*pos], arg, remaining_args),
+            Some(&(_, pos)) => self.fill_slot(pos, arg, value, remaining_args),
             None => self
-                .try_parse_global(arg, remaining_args)
-                .unwrap_or_else(|| Err(Error::UnknownArgument(OsString::from(arg)))),
+                .try_parse_version(arg, value)
+                .or_else(|| self.try_parse_global(arg, value, remaining_args))
+                .or_else(|| self.try_parse_abbreviation(arg, value, remaining_args))
+                .unwrap_or_else(|| Err(self.unknown_option(arg))),
+        }
+    }
+
+    /// Parses the `-V` or `--version` option, if the command has a version.
+    /// Returns `None` if `arg` isn't one of them.
+    fn try_parse_version(&mut self, arg: &str, value: Option<&OsStr>) -> Option<Result<(), Error>> {
+        if !matches!(arg, "-V" | "--version") || self.help.version.is_none() {
+            return None;
+        }
+        if value.is_some() {
+            return Some(Err(Error::UnexpectedArgValue(arg.to_owned())));
+        }
+        self.version_requested = self.help.version;
+        Some(Ok(()))
+    }
+
+    /// Returns [`Error::UnknownArgument`] for the given option, or
+    /// [`Error::UnknownArgumentSuggestion`] if suggestions are enabled and
+    /// there's a similar long option in this instance or a global option in
+    /// the parent.
+    fn unknown_option(&self, arg: &str) -> Error {
+        #[cfg(feature = "suggestions")]
+        if arg.starts_with("--") && self.suggestions() {
+            let mut candidates = vec!["--help"];
+            self.collect_long_names("--", false, &mut candidates);
+
+            if let Some(suggestion) = crate::suggestions::did_you_mean(arg, candidates) {
+                return Error::UnknownArgumentSuggestion {
+                    arg: arg.into(),
+                    suggestion,
+                };
+            }
         }
+        Error::UnknownArgument(OsString::from(arg))
+    }
+
+    /// Parses an abbreviated long option (e.g. `--verb` for `--verbose`), if
+    /// abbreviations are enabled. Returns `None` if `arg` isn't a prefix of any
+    /// long option in this instance or a global option in the parent.
+    fn try_parse_abbreviation(
+        &mut self,
+        arg: &str,
+        value: Option<&OsStr>,
+        remaining_args: &mut &[&OsStr],
+    ) -> Option<Result<(), Error>> {
+        // `--` (e.g. from `--=x`) would be a prefix of all long options.
+        if !arg.starts_with("--") || arg.len() == 2 || !self.abbreviations() {
+            return None;
+        }
+        let mut candidates = Vec::new();
+        self.collect_long_names(arg, false, &mut candidates);
+
+        match candidates[..] {
+            [] => None,
+            [name] => Some(self.parse(name, value, remaining_args)),
+            // An option and its aliases (e.g. `--col` for `--color` and
+            // `--colour`) are not ambiguous.
+            [name, ..] if self.same_local_slot(&candidates) => {
+                Some(self.parse(name, value, remaining_args))
+            }
+            _ => Some(Err(Error::AmbiguousOption {
+                arg: arg.to_owned(),
+                candidates,
+            })),
+        }
+    }
+
+    /// Returns `true` if all the given option names are defined in this
+    /// instance and refer to the same slot.
+    fn same_local_slot(&self, names: &[&str]) -> bool {
+        let slot = |name: &&str| {
+            self.arg_to_slot
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|e| e.1)
+        };
+        let first = slot(&names[0]);
+        first.is_some() && names.iter().all(|name| slot(name) == first)
+    }
+
+    /// Appends long names of the options in this instance (or only the global
+    /// options if `global_only` is `true`) and recursively of the global
+    /// options in the parent that start with the given `prefix`.
+    fn collect_long_names(&self, prefix: &str, global_only: bool, out: &mut Vec<&'static str>) {
+        for (name, pos) in self.arg_to_slot {
+            if name.starts_with("--")
+                && name.starts_with(prefix)
+                && (!global_only || self.slots_global[*pos])
+                && !out.contains(name)
+            {
+                out.push(name);
+            }
+        }
+        if let Some(parent) = &self.parent {
+            parent.global_long_names(prefix, out);
+        }
+    }
+
+    /// Parses a cluster of short options (e.g. `-ab`) the way getopt does:
+    /// `-ab` is parsed as `-a -b`, `-an 5`, `-an5` and `-an=5` as `-a -n 5`,
+    /// but `-na 5` as `-n a 5`. As soon as an option that takes a value is
+    /// found, the rest of the cluster (if any) becomes its value.
+    ///
+    /// - `arg`: The current argument being parsed (e.g. `-an5`).
+    /// - `remaining_args`: The remaining command line arguments. This slice
+    ///   will be advanced forwards if the last option in the cluster takes a
+    ///   value argument.
+    fn parse_short_cluster(
+        &mut self,
+        arg: &OsStr,
+        remaining_args: &mut &[&OsStr],
+    ) -> Result<(), Error> {
+        let bytes = arg.as_encoded_bytes();
+
+        for index in 1..bytes.len() {
+            // Short names are always ASCII, so anything else is unknown.
+            if !bytes[index].is_ascii() {
+                let rest = String::from_utf8_lossy(&bytes[index..]);
+                let short = rest.chars().next().unwrap_or_default();
+                return Err(Error::UnknownArgument(format!("-{}", short).into()));
+            }
+            let short = format!("-{}", bytes[index] as char);
+            let rest = os_str_after(arg, index);
+
+            match rest.as_encoded_bytes().first() {
+                None => return self.parse(&short, None, remaining_args),
+                Some(b'=') => {
+                    return self.parse(&short, Some(os_str_after(rest, 0)), remaining_args)
+                }
+                Some(_) if self.takes_value(&short) => {
+                    return self.parse(&short, Some(rest), remaining_args)
+                }
+                Some(_) => self.parse(&short, None, &mut (&[] as &[&OsStr]))?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the given argument refers to an option in this
+    /// instance or a global option in the parent, including abbreviations of
+    /// long options. Only the first option of a short cluster is checked, and
+    /// an attached value is ignored.
+    fn is_known_option(&self, arg: &str) -> bool {
+        let name = if arg.starts_with("--") {
+            split_attached_value(OsStr::new(arg)).0
+        } else {
+            arg.get(..2).unwrap_or(arg)
+        };
+        if self.arg_to_slot.iter().any(|(n, _)| *n == name)
+            || self
+                .parent
+                .as_ref()
+                .is_some_and(|p| p.global_takes_value(name).is_some())
+        {
+            return true;
+        }
+        if !name.starts_with("--") || !self.abbreviations() {
+            return false;
+        }
+        let mut candidates = Vec::new();
+        self.collect_long_names(name, false, &mut candidates);
+        !candidates.is_empty()
+    }
+
+    /// Returns `true` if there's a numeric short option (e.g. `-1`) in this
+    /// instance or a global one in the parent.
+    fn has_numeric_short_options(&self) -> bool {
+        ('0'..='9').any(|digit| self.is_known_option(&format!("-{}", digit)))
+    }
+
+    /// Returns `true` if the given option, or a global option of the same
+    /// name in the parent, requires a value, i.e. it's not a switch and
+    /// doesn't have `default_missing`.
+    fn takes_value(&self, arg: &str) -> bool {
+        match self.arg_to_slot.iter().find(|(name, _)| *name == arg) {
+            Some((_, pos)) => self.requires_value(*pos),
+            None => self
+                .parent
+                .as_ref()
+                .and_then(|p| p.global_takes_value(arg))
+                .unwrap_or(false),
+        }
+    }
+
+    /// Returns `true` if the option at the given slot position requires
+    /// a value, i.e. it's not a switch and doesn't have `default_missing`.
+    fn requires_value(&self, pos: usize) -> bool {
+        matches!(self.slots[pos], ParseStructOption::Value(_))
+            && self.default_missing(pos).is_none()
+    }
+
+    /// Returns the `default_missing` value of the option at the given slot
+    /// position, if any.
+    fn default_missing(&self, pos: usize) -> Option<&'static str> {
+        self.slots_default_missing.and_then(|table| table[pos])
     }
 
     fn fill_slot(
-        slot: &mut ParseStructOption<'a>,
+        &mut self,
+        pos: usize,
         arg: &str,
+        value: Option<&OsStr>,
         remaining_args: &mut &[&OsStr],
     ) -> Result<(), Error> {
-        match slot {
-            ParseStructOption::Flag(ref mut b) => b.set_flag(arg),
+        self.slots_provided[pos] = true;
+        let default_missing = self.default_missing(pos);
+
+        match &mut self.slots[pos] {
+            ParseStructOption::Value(ref mut pvs)
+                if self.slots_overridable.is_some_and(|table| table[pos]) =>
+            {
+                pvs.unset()
+            }
+            _ => {}
+        }
+
+        match &mut self.slots[pos] {
+            ParseStructOption::Flag(ref mut b) => {
+                if value.is_some() {
+                    return Err(Error::UnexpectedArgValue(arg.to_owned()));
+                }
+                b.set_flag(arg);
+            }
+            ParseStructOption::Value(ref mut pvs) if pvs.arity() > 1 => {
+                // The attached value (if any) is the first one, the rest is
+                // taken from the following arguments.
+                let expected = pvs.arity();
+                let needed = expected - usize::from(value.is_some());
+                if remaining_args.len() < needed {
+                    return Err(Error::MissingArgValues {
+                        arg: arg.to_owned(),
+                        expected,
+                    });
+                }
+                let values: Vec<&OsStr> = value
+                    .into_iter()
+                    .chain(remaining_args[..needed].iter().copied())
+                    .collect();
+                *remaining_args = &remaining_args[needed..];
+
+                pvs.fill_slot_values(arg, &values)?;
+            }
             ParseStructOption::Value(ref mut pvs) => {
-                let value = remaining_args
-                    .first()
-                    .ok_or_else(|| Error::MissingArgValue(arg.to_owned()))?;
-                *remaining_args = &remaining_args[1..];
+                let value = match (value, default_missing) {
+                    (Some(value), _) => value,
+                    (None, Some(default_missing)) => OsStr::new(default_missing),
+                    (None, None) => {
+                        let value = remaining_args
+                            .first()
+                            .ok_or_else(|| Error::MissingArgValue(arg.to_owned()))?;
+                        *remaining_args = &remaining_args[1..];
+                        value
+                    }
+                };
                 pvs.fill_slot(arg, value)?;
             }
         }
         Ok(())
     }
+
+    /// Fills the slots of the options that haven't been provided on the command
+    /// line (nor from their environment variables) from the configuration
+    /// source. A slot is not filled if it conflicts with a slot provided with
+    /// a higher precedence, see [`fill_from_env`].
+    fn fill_from_config(&mut self, config: &dyn ConfigSource) -> Result<(), Error> {
+        let provided_on_cli_or_env = self.slots_provided.to_vec();
+
+        for pos in 0..self.slots.len() {
+            if self.slots_provided[pos] || self.conflicts_with_any(pos, &provided_on_cli_or_env) {
+                continue;
+            }
+            // The key is the first long name of the option without the leading
+            // dashes (e.g. `foo` for `--foo`).
+            let key = match self
+                .arg_to_slot
+                .iter()
+                .find(|(name, i)| *i == pos && name.starts_with("--"))
+            {
+                Some((long_name, _)) => &long_name[2..],
+                None => continue,
+            };
+            for value in config.values(key) {
+                fill_slot_fallback(&mut self.slots[pos], key, OsStr::new(value.as_ref())).map_err(
+                    |msg| Error::ParseConfigValue {
+                        source: config.name().to_owned(),
+                        key: key.to_owned(),
+                        value: OsString::from(value.as_ref()),
+                        msg,
+                    },
+                )?;
+                self.slots_provided[pos] = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the slot at `pos` conflicts with any of the slots
+    /// marked in `provided` (see [`ParseStructOptions::slots_conflicts`]).
+    fn conflicts_with_any(&self, pos: usize, provided: &[bool]) -> bool {
+        self.slots_conflicts
+            .iter()
+            .any(|&(a, b)| (a == pos && provided[b]) || (b == pos && provided[a]))
+    }
 }
 
 #[doc(hidden)]
@@ -171,14 +702,40 @@ pub trait ParseGlobalOptions {
     /// is still not found, it returns `None`.
     ///
     /// - `arg`: The current option argument being parsed (e.g. `--foo`).
+    /// - `value`: The value attached to the option argument, if any.
     /// - `remaining_args`: The remaining command line arguments. This slice
-    ///   will be advanced forwards if the option takes a value argument.
+    ///   will be advanced forwards if the option takes a value argument and
+    ///   `value` is `None`.
     fn try_parse_global(
         &mut self,
         arg: &str,
+        value: Option<&OsStr>,
         remaining_args: &mut &[&OsStr],
     ) -> Option<Result<(), Error>>;
 
+    /// Returns `Some(true)` if the given global option requires a value,
+    /// `Some(false)` if it doesn't (it's a switch or has `default_missing`),
+    /// or `None` if it's not found in _self_ nor
+    /// recursively in the parent.
+    fn global_takes_value(&self, arg: &str) -> Option<bool>;
+
+    /// Appends long names of the global options specified on this instance and
+    /// recursively on the parent that start with the given `prefix`.
+    fn global_long_names(&self, prefix: &str, out: &mut Vec<&'static str>);
+
+    /// Returns `true` if abbreviations of long options are enabled on this
+    /// instance or recursively on the parent.
+    fn abbreviations(&self) -> bool;
+
+    /// Returns `true` if suggestions for unknown arguments are enabled on this
+    /// instance or recursively on the parent.
+    fn suggestions(&self) -> bool;
+
+    /// Fills the slots of the options in `options` that haven't been provided
+    /// on the command line (nor from their environment variables) from the
+    /// configuration source, if specified on the root of the chain.
+    fn fill_from_config(&self, options: &mut ParseStructOptions<'_, '_>) -> Result<(), Error>;
+
     /// Returns a vector representing global options specified on this instance
     /// and recursively on the parent. This is used for generating a help
     /// message.
@@ -189,29 +746,342 @@ impl ParseGlobalOptions for ParseStructOptions<'_, '_> {
     fn try_parse_global(
         &mut self,
         arg: &str,
+        value: Option<&OsStr>,
         remaining_args: &mut &[&OsStr],
     ) -> Option<Result<(), Error>> {
         self.arg_to_slot
             .iter()
             .find(|(name, pos)| *name == arg && self.slots_global[*pos])
-            .map(|(_, pos)| Self::fill_slot(&mut self.slots[*pos], arg, remaining_args))
+            .map(|&(_, pos)| self.fill_slot(pos, arg, value, remaining_args))
             .or_else(|| {
                 self.parent
                     .as_mut()
-                    .and_then(|p| p.try_parse_global(arg, remaining_args))
+                    .and_then(|p| p.try_parse_global(arg, value, remaining_args))
             })
     }
 
+    fn global_takes_value(&self, arg: &str) -> Option<bool> {
+        self.arg_to_slot
+            .iter()
+            .find(|(name, pos)| *name == arg && self.slots_global[*pos])
+            .map(|(_, pos)| self.requires_value(*pos))
+            .or_else(|| self.parent.as_ref().and_then(|p| p.global_takes_value(arg)))
+    }
+
+    fn global_long_names(&self, prefix: &str, out: &mut Vec<&'static str>) {
+        self.collect_long_names(prefix, true, out);
+    }
+
+    fn abbreviations(&self) -> bool {
+        self.abbreviations || self.parent.as_ref().is_some_and(|p| p.abbreviations())
+    }
+
+    fn suggestions(&self) -> bool {
+        self.suggestions || self.parent.as_ref().is_some_and(|p| p.suggestions())
+    }
+
+    fn fill_from_config(&self, options: &mut ParseStructOptions<'_, '_>) -> Result<(), Error> {
+        self.parent
+            .as_ref()
+            .map_or(Ok(()), |p| p.fill_from_config(options))
+    }
+
     fn global_options(&self) -> Vec<&'static OptionArgInfo> {
         let mut opts = self
             .parent
             .as_ref()
             .map_or_else(Vec::new, |p| p.global_options());
-        opts.extend(self.help.options.iter().filter(|o| o.global));
+        opts.extend(self.help.all_options().into_iter().filter(|o| o.global));
         opts
     }
 }
 
+/// The root of the [`ParseGlobalOptions`] chain that provides a configuration
+/// source, but no options.
+pub(crate) struct ParseConfig<'a>(pub(crate) &'a dyn ConfigSource);
+
+impl ParseGlobalOptions for ParseConfig<'_> {
+    fn try_parse_global(
+        &mut self,
+        _arg: &str,
+        _value: Option<&OsStr>,
+        _remaining_args: &mut &[&OsStr],
+    ) -> Option<Result<(), Error>> {
+        None
+    }
+
+    fn global_takes_value(&self, _arg: &str) -> Option<bool> {
+        None
+    }
+
+    fn global_long_names(&self, _prefix: &str, _out: &mut Vec<&'static str>) {}
+
+    fn abbreviations(&self) -> bool {
+        false
+    }
+
+    fn suggestions(&self) -> bool {
+        false
+    }
+
+    fn fill_from_config(&self, options: &mut ParseStructOptions<'_, '_>) -> Result<(), Error> {
+        options.fill_from_config(self.0)
+    }
+
+    fn global_options(&self) -> Vec<&'static OptionArgInfo> {
+        Vec::new()
+    }
+}
+
+/// A struct whose options and positional arguments can be embedded into
+/// another struct using `#[argp(flatten)]`. This trait is implemented by the
+/// derive macro for all structs.
+#[doc(hidden)]
+pub trait Flatten: Sized {
+    /// The option names of the struct, including its flattened structs.
+    const ARG_NAMES: ArgNames;
+
+    /// Whether the struct has a subcommand, which isn't allowed for flattened
+    /// structs.
+    const HAS_SUBCOMMAND: bool;
+
+    /// Whether the struct or any of its flattened structs has a global option,
+    /// which is allowed only in a command with a subcommand.
+    const HAS_GLOBAL: bool;
+
+    /// Creates the parsing tables of the struct (including its flattened
+    /// structs), passes them to the `parse` function and then constructs the
+    /// struct from the parsed values.
+    fn _from_flattened(
+        parse: impl FnOnce(ParseStructFragment<'_>) -> Result<(), EarlyExit>,
+    ) -> Result<Self, EarlyExit>;
+}
+
+/// The option names of a struct (as in [`ParseStructOptions::arg_to_slot`])
+/// and of its flattened structs, recursively. This is used to detect
+/// duplicate names at compile time.
+#[doc(hidden)]
+pub struct ArgNames {
+    pub names: &'static [&'static str],
+    pub flattened: &'static [&'static ArgNames],
+}
+
+impl ArgNames {
+    /// Returns the total number of names, including the flattened ones.
+    const fn len(&self) -> usize {
+        let mut len = self.names.len();
+        let mut i = 0;
+        while i < self.flattened.len() {
+            len += self.flattened[i].len();
+            i += 1;
+        }
+        len
+    }
+
+    /// Returns the name at the given index, counting the flattened names after
+    /// the own ones.
+    const fn get(&self, mut index: usize) -> &'static str {
+        if index < self.names.len() {
+            return self.names[index];
+        }
+        index -= self.names.len();
+
+        let mut i = 0;
+        while index >= self.flattened[i].len() {
+            index -= self.flattened[i].len();
+            i += 1;
+        }
+        self.flattened[i].get(index)
+    }
+
+    /// Checks that all the names are unique, panics otherwise. This is used in
+    /// a constant context generated for `#[derive(FromArgs)]` structs with
+    /// flattened structs, so duplicates are reported at compile time.
+    pub const fn ensure_unique(&self) {
+        let len = self.len();
+        let mut i = 0;
+        while i < len {
+            let mut j = i + 1;
+            while j < len {
+                if str_eq(self.get(i), self.get(j)) {
+                    panic!("The option names of flattened structs must be unique");
+                }
+                j += 1;
+            }
+            i += 1;
+        }
+    }
+}
+
+/// See the `response_files` parameter of [`parse_struct_args`].
+#[doc(hidden)]
+pub type ExpandResponseFiles = fn(&[&OsStr]) -> Result<Vec<OsString>, Error>;
+
+/// See [`ParseStructFragment::fill_from_env`].
+#[doc(hidden)]
+pub type EnvFallback = fn(&mut ParseStructOptions<'_, '_>) -> Result<(), Error>;
+
+/// The parsing tables of a struct, passed from [`Flatten::_from_flattened`] to
+/// [`parse_struct_args`], or merged with the tables of the parent struct if
+/// this struct is flattened (see [`ParseStructFragment::flatten`]).
+#[doc(hidden)]
+pub struct ParseStructFragment<'a> {
+    /// See [`ParseStructOptions::arg_to_slot`].
+    pub arg_to_slot: &'a [(&'static str, usize)],
+    /// See [`ParseStructOptions::slots`].
+    pub slots: &'a mut [ParseStructOption<'a>],
+    /// See [`ParseStructOptions::slots_global`].
+    pub slots_global: &'a [bool],
+    /// See [`ParseStructOptions::slots_env`].
+    pub slots_env: Option<&'a [Option<&'static str>]>,
+    /// See [`ParseStructOptions::slots_default_missing`].
+    pub slots_default_missing: Option<&'a [Option<&'static str>]>,
+    /// See [`ParseStructOptions::slots_overridable`].
+    pub slots_overridable: Option<&'a [bool]>,
+    /// See [`ParseStructOptions::slots_conflicts`].
+    pub slots_conflicts: &'a [(usize, usize)],
+    /// See [`ParseStructOptions::slots_provided`].
+    pub slots_provided: &'a mut [bool],
+    /// The function filling the options from their environment variables
+    /// ([`fill_from_env`]), or `None` if no option has an environment
+    /// variable.
+    pub fill_from_env: Option<EnvFallback>,
+    /// See [`ParseStructPositionals::positionals`].
+    pub positionals: &'a mut [ParseStructPositional<'a>],
+    /// See [`ParseStructPositionals::repeating`].
+    pub repeating_positional: Option<usize>,
+    /// See [`ParseStructPositionals::last_is_greedy`].
+    pub last_positional_is_greedy: bool,
+    /// The subcommand of the struct, if any. Flattened structs cannot have a
+    /// subcommand.
+    pub subcommand: Option<ParseStructSubCommand<'a>>,
+}
+
+impl ParseStructFragment<'_> {
+    /// Merges the tables of the `other` (flattened) struct into this one and
+    /// passes the result to the function `f`. The positional arguments of the
+    /// `other` struct are inserted at `positionals_index`.
+    pub fn flatten<R>(
+        mut self,
+        other: ParseStructFragment<'_>,
+        positionals_index: usize,
+        f: impl FnOnce(ParseStructFragment<'_>) -> R,
+    ) -> R {
+        debug_assert!(other.subcommand.is_none(), "flattened struct cannot have a subcommand");
+        debug_assert!(
+            other
+                .arg_to_slot
+                .iter()
+                .all(|(name, _)| self.arg_to_slot.iter().all(|(n, _)| n != name)),
+            "flattened struct has an option with the same name as the parent struct"
+        );
+
+        let offset = self.slots.len();
+        let other_len = other.slots.len();
+        let arg_to_slot: Vec<_> = (self.arg_to_slot.iter().copied())
+            .chain(
+                other
+                    .arg_to_slot
+                    .iter()
+                    .map(|&(name, pos)| (name, pos + offset)),
+            )
+            .collect();
+        let mut slots: Vec<_> = (self.slots.iter_mut().map(ParseStructOption::reborrow))
+            .chain(other.slots.iter_mut().map(ParseStructOption::reborrow))
+            .collect();
+        let mut slots_provided = [&*self.slots_provided, &*other.slots_provided].concat();
+
+        debug_assert!(
+            self.repeating_positional.is_none() || other.repeating_positional.is_none(),
+            "only one positional argument may be repeating"
+        );
+        let repeating_positional = match self.repeating_positional {
+            Some(i) if i >= positionals_index => Some(i + other.positionals.len()),
+            Some(i) => Some(i),
+            None => other.repeating_positional.map(|i| i + positionals_index),
+        };
+        let last_positional_is_greedy =
+            if positionals_index == self.positionals.len() && !other.positionals.is_empty() {
+                other.last_positional_is_greedy
+            } else {
+                self.last_positional_is_greedy
+            };
+        let mut positionals: Vec<_> = self
+            .positionals
+            .iter_mut()
+            .map(ParseStructPositional::reborrow)
+            .collect();
+        positionals.splice(
+            positionals_index..positionals_index,
+            other
+                .positionals
+                .iter_mut()
+                .map(ParseStructPositional::reborrow),
+        );
+
+        let res = f(ParseStructFragment {
+            arg_to_slot: &arg_to_slot,
+            slots: &mut slots,
+            slots_global: &[self.slots_global, other.slots_global].concat(),
+            slots_env: concat_optional(self.slots_env, offset, other.slots_env, other_len)
+                .as_deref(),
+            slots_default_missing: concat_optional(
+                self.slots_default_missing,
+                offset,
+                other.slots_default_missing,
+                other_len,
+            )
+            .as_deref(),
+            slots_overridable: concat_optional(
+                self.slots_overridable,
+                offset,
+                other.slots_overridable,
+                other_len,
+            )
+            .as_deref(),
+            slots_conflicts: &(self.slots_conflicts.iter().copied())
+                .chain(
+                    other
+                        .slots_conflicts
+                        .iter()
+                        .map(|&(a, b)| (a + offset, b + offset)),
+                )
+                .collect::<Vec<_>>(),
+            slots_provided: &mut slots_provided,
+            fill_from_env: self.fill_from_env.or(other.fill_from_env),
+            positionals: &mut positionals,
+            repeating_positional,
+            last_positional_is_greedy,
+            subcommand: self
+                .subcommand
+                .as_mut()
+                .map(ParseStructSubCommand::reborrow),
+        });
+
+        let (provided, other_provided) = slots_provided.split_at(offset);
+        self.slots_provided.copy_from_slice(provided);
+        other.slots_provided.copy_from_slice(other_provided);
+
+        res
+    }
+}
+
+/// Concatenates two optional tables of the given lengths, filling a missing
+/// one with default values. Returns `None` if both are missing.
+fn concat_optional<T: Copy + Default>(
+    a: Option<&[T]>,
+    a_len: usize,
+    b: Option<&[T]>,
+    b_len: usize,
+) -> Option<Vec<T>> {
+    if a.is_none() && b.is_none() {
+        return None;
+    }
+    let a = a.map_or_else(|| vec![T::default(); a_len], <[T]>::to_vec);
+    let b = b.map_or_else(|| vec![T::default(); b_len], <[T]>::to_vec);
+    Some([a, b].concat())
+}
+
 /// `--` or `-` options, including a mutable reference to their value.
 #[doc(hidden)]
 pub enum ParseStructOption<'a> {
@@ -222,11 +1092,27 @@ pub enum ParseStructOption<'a> {
     Value(&'a mut dyn ParseValueSlot),
 }
 
+impl ParseStructOption<'_> {
+    fn reborrow(&mut self) -> ParseStructOption<'_> {
+        match self {
+            ParseStructOption::Flag(flag) => ParseStructOption::Flag(&mut **flag),
+            ParseStructOption::Value(value) => ParseStructOption::Value(&mut **value),
+        }
+    }
+}
+
 #[doc(hidden)]
 pub struct ParseStructPositionals<'a> {
     pub positionals: &'a mut [ParseStructPositional<'a>],
-    pub last_is_repeating: bool,
+    /// The index of the repeating positional argument (`Vec`), if any. It may
+    /// be followed only by required positional arguments.
+    pub repeating: Option<usize>,
     pub last_is_greedy: bool,
+    /// The arguments for the repeating positional argument and the ones
+    /// following it, if it's not the last one. These can be assigned only
+    /// after all the arguments are collected, see
+    /// [`ParseStructPositionals::assign_deferred`].
+    deferred: Vec<OsString>,
 }
 
 impl ParseStructPositionals<'_> {
@@ -238,23 +1124,69 @@ impl ParseStructPositionals<'_> {
     /// Returns `true` if non-positional argument parsing should stop after this
     /// one.
     fn parse(&mut self, index: &mut usize, arg: &OsStr) -> Result<bool, Error> {
-        if *index < self.positionals.len() {
-            self.positionals[*index].parse(arg)?;
-
-            if self.last_is_repeating && *index == self.positionals.len() - 1 {
+        if *index >= self.positionals.len() {
+            return Err(Error::UnknownArgument(arg.to_owned()));
+        }
+        if self.repeating == Some(*index) {
+            if *index == self.positionals.len() - 1 {
+                self.positionals[*index].parse(arg)?;
                 // Don't increment position if we're at the last arg *and* the
                 // last arg is repeating. If it's also remainder, halt
                 // non-option processing after this.
-                Ok(self.last_is_greedy)
-            } else {
-                // If it is repeating, though, increment the index and continue
-                // processing options.
-                *index += 1;
-                Ok(false)
+                return Ok(self.last_is_greedy);
             }
-        } else {
-            Err(Error::UnknownArgument(arg.to_owned()))
+            // Don't increment position either if the repeating arg is not the
+            // last one, it's not known yet how many of the remaining args
+            // belong to it.
+            self.deferred.push(arg.to_owned());
+            return Ok(false);
+        }
+        self.positionals[*index].parse(arg)?;
+        *index += 1;
+
+        Ok(false)
+    }
+
+    /// Returns `true` if the given argument starting with `-` should be parsed
+    /// as a value of the positional argument at `index` rather than as an
+    /// option. That's if the positional argument allows hyphen values and
+    /// `arg` isn't a known option, or if `arg` is a negative number (e.g. `-5`
+    /// or `-0.5`) and there are no numeric short options.
+    fn takes_hyphen_value(
+        &self,
+        index: usize,
+        arg: &str,
+        options: &ParseStructOptions<'_, '_>,
+    ) -> bool {
+        let positional = match self.positionals.get(index) {
+            Some(positional) if arg != "--" => positional,
+            _ => return false,
+        };
+        (positional.allow_hyphen_values && !options.is_known_option(arg))
+            || (is_negative_number(arg) && !options.has_numeric_short_options())
+    }
+
+    /// Assigns the deferred arguments (see [`ParseStructPositionals::parse`])
+    /// from both ends: the last ones to the positional arguments following the
+    /// repeating one, and the rest to the repeating one. If there are not
+    /// enough arguments, the repeating one and the last ones are left missing.
+    fn assign_deferred(&mut self) -> Result<(), Error> {
+        let index = match self.repeating {
+            Some(index) if !self.deferred.is_empty() => index,
+            _ => return Ok(()),
+        };
+        let (repeating, following) = self.positionals[index..]
+            .split_first_mut()
+            .expect("repeating positional index out of bounds");
+
+        let split = self.deferred.len().saturating_sub(following.len());
+        for arg in &self.deferred[..split] {
+            repeating.parse(arg)?;
+        }
+        for (positional, arg) in following.iter_mut().zip(&self.deferred[split..]) {
+            positional.parse(arg)?;
         }
+        Ok(())
     }
 }
 
@@ -265,9 +1197,21 @@ pub struct ParseStructPositional<'a> {
 
     /// The function to parse the positional.
     pub slot: &'a mut dyn ParseValueSlot,
+
+    /// Whether the positional accepts values starting with `-` that are not
+    /// known options.
+    pub allow_hyphen_values: bool,
 }
 
 impl ParseStructPositional<'_> {
+    fn reborrow(&mut self) -> ParseStructPositional<'_> {
+        ParseStructPositional {
+            name: self.name,
+            slot: &mut *self.slot,
+            allow_hyphen_values: self.allow_hyphen_values,
+        }
+    }
+
     /// Parses a positional argument.
     ///
     /// - `arg`: The argument supplied by the user.
@@ -276,6 +1220,84 @@ impl ParseStructPositional<'_> {
     }
 }
 
+/// Checks that the names and aliases of the given commands are unique, panics
+/// otherwise. This is used in a constant context generated for
+/// `#[derive(FromArgs)]` enums, so duplicates are reported at compile time.
+#[doc(hidden)]
+pub const fn ensure_unique_commands(
+    commands: &'static [&'static CommandInfo],
+) -> &'static [&'static CommandInfo] {
+    const fn names(cmd: &CommandInfo, index: usize) -> Option<&str> {
+        match index {
+            0 => Some(cmd.name),
+            i if i <= cmd.aliases.len() => Some(cmd.aliases[i - 1]),
+            i if i <= cmd.aliases.len() + cmd.hidden_aliases.len() => {
+                Some(cmd.hidden_aliases[i - 1 - cmd.aliases.len()])
+            }
+            _ => None,
+        }
+    }
+
+    // Compares each name with all the names that follow it, in the same
+    // command and in the next ones.
+    let mut i = 0;
+    while i < commands.len() {
+        let mut n = 0;
+        while let Some(name) = names(commands[i], n) {
+            let (mut j, mut m) = (i, n + 1);
+            while j < commands.len() {
+                while let Some(other) = names(commands[j], m) {
+                    if str_eq(name, other) {
+                        panic!("The subcommand names and aliases must be unique");
+                    }
+                    m += 1;
+                }
+                j += 1;
+                m = 0;
+            }
+            n += 1;
+        }
+        i += 1;
+    }
+    commands
+}
+
+/// Checks that the `value` of a `required_if` attribute is one of the
+/// `possible_values` of the referred option, if it has any, panics otherwise.
+/// This is used in a constant context generated for `#[derive(FromArgs)]`
+/// structs, so an invalid value is reported at compile time.
+#[doc(hidden)]
+pub const fn ensure_possible_value(possible_values: &[&str], value: &str) {
+    if possible_values.is_empty() {
+        return;
+    }
+    let mut i = 0;
+    while i < possible_values.len() {
+        if str_eq(possible_values[i], value) {
+            return;
+        }
+        i += 1;
+    }
+    panic!("The value of `required_if` must be one of the possible values of the option");
+}
+
+/// Returns `true` if the given strings are equal. This is a `const` version of
+/// `a == b`.
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
 /// A type to simplify parsing struct subcommands.
 ///
 /// This indirection is necessary to allow abstracting over [`FromArgs`]
@@ -297,6 +1319,14 @@ pub struct ParseStructSubCommand<'a> {
 }
 
 impl ParseStructSubCommand<'_> {
+    fn reborrow(&mut self) -> ParseStructSubCommand<'_> {
+        ParseStructSubCommand {
+            subcommands: self.subcommands,
+            dynamic_subcommands: self.dynamic_subcommands,
+            parse_func: &mut *self.parse_func,
+        }
+    }
+
     fn parse(
         &mut self,
         help: bool,
@@ -310,13 +1340,15 @@ impl ParseStructSubCommand<'_> {
             .iter()
             .chain(self.dynamic_subcommands.iter())
         {
-            if subcommand.name == arg {
+            if subcommand.matches(arg) {
                 let mut command = cmd_name.to_owned();
                 command.push(subcommand.name);
 
+                // The help subcommand keyword and the short option may be
+                // renamed or disabled in the subcommand, `--help` can't.
                 let prepended_help;
                 let remaining_args = if help {
-                    prepended_help = [&[OsStr::new("help")], remaining_args].concat();
+                    prepended_help = [&[OsStr::new("--help")], remaining_args].concat();
                     &prepended_help
                 } else {
                     remaining_args
@@ -330,17 +1362,65 @@ impl ParseStructSubCommand<'_> {
 
         Ok(false)
     }
+
+    /// Returns [`Error::UnknownArgument`] for the given argument, or
+    /// [`Error::UnknownArgumentSuggestion`] if there's a similar subcommand.
+    #[cfg(feature = "suggestions")]
+    fn unknown_subcommand(&self, arg: OsString) -> Error {
+        let names = self
+            .subcommands
+            .iter()
+            .chain(self.dynamic_subcommands.iter())
+            .flat_map(|cmd| std::iter::once(cmd.name).chain(cmd.aliases.iter().copied()));
+
+        match arg
+            .to_str()
+            .and_then(|s| crate::suggestions::did_you_mean(s, names))
+        {
+            Some(suggestion) => Error::UnknownArgumentSuggestion { arg, suggestion },
+            None => Error::UnknownArgument(arg),
+        }
+    }
 }
 
 #[doc(hidden)]
 pub trait ParseFlag {
     fn set_flag(&mut self, arg: &str);
+    fn unset_flag(&mut self);
 }
 
 impl<T: Flag> ParseFlag for T {
     fn set_flag(&mut self, _arg: &str) {
         <T as Flag>::set_flag(self);
     }
+
+    fn unset_flag(&mut self) {
+        <T as Flag>::unset_flag(self);
+    }
+}
+
+/// A slot for a switch that can also be negated using the `--no-` prefix (e.g.
+/// `--no-foo`).
+#[doc(hidden)]
+pub struct NegatableFlag<T> {
+    /// The slot for the flag value.
+    pub slot: T,
+    /// The negative form of the switch (e.g. `--no-foo`).
+    pub negative: &'static str,
+}
+
+impl<T: Flag> ParseFlag for NegatableFlag<T> {
+    fn set_flag(&mut self, arg: &str) {
+        if arg == self.negative {
+            self.slot.unset_flag();
+        } else {
+            self.slot.set_flag();
+        }
+    }
+
+    fn unset_flag(&mut self) {
+        self.slot.unset_flag();
+    }
 }
 
 /// A trait for for slots that reserve space for a value and know how to parse
@@ -352,6 +1432,23 @@ impl<T: Flag> ParseFlag for T {
 #[doc(hidden)]
 pub trait ParseValueSlot {
     fn fill_slot(&mut self, arg: &str, value: &OsStr) -> Result<(), Error>;
+
+    /// Clears the value of a non-repeating slot, so it can be filled again.
+    /// Repeating slots are left as is.
+    fn unset(&mut self) {}
+
+    /// Returns the number of values that the option takes per occurrence.
+    fn arity(&self) -> usize {
+        1
+    }
+
+    /// Fills the slot with the values of a single occurrence of an option with
+    /// the arity greater than 1.
+    fn fill_slot_values(&mut self, arg: &str, values: &[&OsStr]) -> Result<(), Error> {
+        values
+            .iter()
+            .try_for_each(|value| self.fill_slot(arg, value))
+    }
 }
 
 /// The concrete type implementing the [`ParseValueSlot`] trait.
@@ -382,6 +1479,10 @@ impl<T> ParseValueSlot for ParseValueSlotTy<Option<T>, T> {
         self.slot = Some(parsed);
         Ok(())
     }
+
+    fn unset(&mut self) {
+        self.slot = None;
+    }
 }
 
 /// `ParseValueSlotTy<Vec<T>, T>` is used as the slot for repeating arguments.
@@ -397,6 +1498,173 @@ impl<T> ParseValueSlot for ParseValueSlotTy<Vec<T>, T> {
     }
 }
 
+/// A slot for options that take a fixed number of values per occurrence (e.g.
+/// `--rename <old> <new>`), parsed into a tuple or an array.
+///
+/// - `T` is the type to be parsed from `arity` strings.
+/// - `Slot` is the type of the container that can hold a value or values of
+///   type `T`.
+#[doc(hidden)]
+pub struct ParseMultiValueSlotTy<Slot, T> {
+    /// The slot for a parsed value.
+    pub slot: Slot,
+    /// The function to parse the value from `arity` strings.
+    pub parse_func: fn(&str, &[&OsStr]) -> Result<T, Error>,
+    /// The number of values per occurrence.
+    pub arity: usize,
+}
+
+impl<Slot, T> ParseMultiValueSlotTy<Slot, T> {
+    /// Splits a single value (e.g. from an environment variable) on
+    /// whitespace and parses it. A value of an option with the arity 1 is
+    /// parsed as is.
+    fn parse_joined(&self, arg: &str, value: &OsStr) -> Result<T, Error> {
+        if self.arity == 1 {
+            return (self.parse_func)(arg, &[value]);
+        }
+        let joined = value.to_str().ok_or_else(|| Error::ParseArgument {
+            arg: arg.to_owned(),
+            value: value.to_owned(),
+            msg: "not a valid UTF-8 string".to_owned(),
+        })?;
+        let values: Vec<&OsStr> = joined.split_whitespace().map(OsStr::new).collect();
+
+        if values.len() != self.arity {
+            return Err(Error::ParseArgument {
+                arg: arg.to_owned(),
+                value: value.to_owned(),
+                msg: format!("expected {} values separated by whitespace", self.arity),
+            });
+        }
+        (self.parse_func)(arg, &values)
+    }
+}
+
+impl<T> ParseValueSlot for ParseMultiValueSlotTy<Option<T>, T> {
+    fn fill_slot(&mut self, arg: &str, value: &OsStr) -> Result<(), Error> {
+        if self.slot.is_some() {
+            return Err(Error::DuplicateOption(arg.to_owned()));
+        }
+        self.slot = Some(self.parse_joined(arg, value)?);
+        Ok(())
+    }
+
+    fn unset(&mut self) {
+        self.slot = None;
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn fill_slot_values(&mut self, arg: &str, values: &[&OsStr]) -> Result<(), Error> {
+        if self.slot.is_some() {
+            return Err(Error::DuplicateOption(arg.to_owned()));
+        }
+        self.slot = Some((self.parse_func)(arg, values)?);
+        Ok(())
+    }
+}
+
+impl<T> ParseValueSlot for ParseMultiValueSlotTy<Vec<T>, T> {
+    fn fill_slot(&mut self, arg: &str, value: &OsStr) -> Result<(), Error> {
+        let parsed = self.parse_joined(arg, value)?;
+        self.slot.push(parsed);
+        Ok(())
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn fill_slot_values(&mut self, arg: &str, values: &[&OsStr]) -> Result<(), Error> {
+        let parsed = (self.parse_func)(arg, values)?;
+        self.slot.push(parsed);
+        Ok(())
+    }
+}
+
+/// Parses the next value of an option with the arity greater than 1 using the
+/// given `parse_func`. This is used in the `parse_func` of
+/// [`ParseMultiValueSlotTy`] generated for each element of a tuple or an
+/// array.
+#[doc(hidden)]
+pub fn parse_next_value<'a, T>(
+    arg: &str,
+    values: &mut impl Iterator<Item = &'a OsStr>,
+    parse_func: fn(&str, &OsStr) -> Result<T, String>,
+) -> Result<T, Error> {
+    let value = values
+        .next()
+        .ok_or_else(|| Error::MissingArgValue(arg.to_owned()))?;
+
+    parse_func(arg, value).map_err(|e| Error::ParseArgument {
+        arg: arg.to_owned(),
+        value: value.to_owned(),
+        msg: e,
+    })
+}
+
+/// A slot for repeating arguments whose values are split on a delimiter (e.g.
+/// `--tags a,b,c`). Each item is parsed separately. The delimiter can be
+/// escaped with a backslash (e.g. `a\,b`), a backslash with another backslash.
+#[doc(hidden)]
+pub struct ParseDelimitedValueSlotTy<T> {
+    /// The slot for parsed values.
+    pub slot: Vec<T>,
+    /// The function to parse a single item from a string.
+    pub parse_func: fn(&str, &OsStr) -> Result<T, String>,
+    /// The delimiter of the items, must be an ASCII character.
+    pub delimiter: char,
+}
+
+impl<T> ParseValueSlot for ParseDelimitedValueSlotTy<T> {
+    fn fill_slot(&mut self, arg: &str, value: &OsStr) -> Result<(), Error> {
+        for item in split_delimited(value, self.delimiter) {
+            let parsed = (self.parse_func)(arg, &item).map_err(|e| Error::ParseArgument {
+                arg: arg.to_owned(),
+                value: item,
+                msg: e,
+            })?;
+            self.slot.push(parsed);
+        }
+        Ok(())
+    }
+}
+
+/// Splits the `value` on the `delimiter`, which must be an ASCII character,
+/// unless it's escaped with a backslash.
+fn split_delimited(value: &OsStr, delimiter: char) -> Vec<OsString> {
+    assert!(delimiter.is_ascii());
+    let delimiter = delimiter as u8;
+
+    let mut items = Vec::new();
+    let mut item = Vec::new();
+    let mut bytes = value.as_encoded_bytes().iter();
+
+    while let Some(&b) = bytes.next() {
+        match b {
+            b'\\' => match bytes.as_slice().first() {
+                Some(&next) if next == delimiter || next == b'\\' => {
+                    item.push(next);
+                    bytes.next();
+                }
+                _ => item.push(b),
+            },
+            b if b == delimiter => items.push(std::mem::take(&mut item)),
+            b => item.push(b),
+        }
+    }
+    items.push(item);
+
+    items
+        .into_iter()
+        // SAFETY: The items are split on an ASCII character and only ASCII
+        // backslashes are removed, so they're valid encoded `OsStr`s.
+        .map(|item| unsafe { OsStr::from_encoded_bytes_unchecked(&item) }.to_owned())
+        .collect()
+}
+
 /// A type which can be the receiver of a [`Flag`].
 #[doc(hidden)]
 pub trait Flag {
@@ -407,6 +1675,10 @@ pub trait Flag {
 
     /// Sets the flag. This function is called when the flag is provided.
     fn set_flag(&mut self);
+
+    /// Unsets the flag. This function is called when the negative form of the
+    /// flag (e.g. `--no-foo`) is provided.
+    fn unset_flag(&mut self);
 }
 
 impl Flag for bool {
@@ -416,6 +1688,9 @@ impl Flag for bool {
     fn set_flag(&mut self) {
         *self = true;
     }
+    fn unset_flag(&mut self) {
+        *self = false;
+    }
 }
 
 impl Flag for Option<bool> {
@@ -426,6 +1701,10 @@ impl Flag for Option<bool> {
     fn set_flag(&mut self) {
         *self = Some(true);
     }
+
+    fn unset_flag(&mut self) {
+        *self = Some(false);
+    }
 }
 
 macro_rules! impl_flag_for_integers {
@@ -438,6 +1717,9 @@ macro_rules! impl_flag_for_integers {
                 fn set_flag(&mut self) {
                     *self = self.saturating_add(1);
                 }
+                fn unset_flag(&mut self) {
+                    *self = 0;
+                }
             }
         )*
     }
@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// SPDX-FileCopyrightText: 2023 Jakub Jirutka <jakub@jirutka.cz>
+
+//! Expansion of response files (`@file` arguments).
+
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+
+/// Expands response files in the given arguments. Each argument of the form
+/// `@path` is replaced with the arguments read from the file at `path`
+/// (relative to the current working directory), the other arguments are kept
+/// as is.
+///
+/// The arguments in a response file are separated by whitespace. Whitespace
+/// can be preserved by enclosing the text in single or double quotes, or by
+/// escaping it with a backslash. Inside double quotes, only `\"` and `\\` are
+/// escape sequences. Response files may refer to other response files, but
+/// not recursively.
+///
+/// This is done automatically for commands with the
+/// `#[argp(response_files)]` attribute.
+///
+/// # Examples
+///
+/// ```
+/// # use std::ffi::OsString;
+/// let args = argp::expand_response_files(&["--foo", "bar"]).unwrap();
+/// assert_eq!(args, [OsString::from("--foo"), OsString::from("bar")]);
+/// ```
+pub fn expand_response_files<S: AsRef<OsStr>>(args: &[S]) -> Result<Vec<OsString>, Error> {
+    let mut out = Vec::with_capacity(args.len());
+    let mut stack = Vec::new();
+
+    for arg in args {
+        expand_arg(arg.as_ref(), &mut stack, &mut out)?;
+    }
+    Ok(out)
+}
+
+/// Appends the given argument to `out`, or the arguments from the response
+/// file if `arg` refers to one.
+///
+/// - `stack`: Canonical paths of the response files being expanded, used to
+///   detect cycles.
+fn expand_arg(arg: &OsStr, stack: &mut Vec<PathBuf>, out: &mut Vec<OsString>) -> Result<(), Error> {
+    let path = match arg.as_encoded_bytes() {
+        [b'@', rest @ ..] if !rest.is_empty() => Path::new(crate::parser::os_str_after(arg, 0)),
+        _ => {
+            out.push(arg.to_owned());
+            return Ok(());
+        }
+    };
+    let error = |msg: String| Error::ResponseFile {
+        path: path.to_owned(),
+        msg,
+    };
+
+    let canonical = path.canonicalize().map_err(|e| error(e.to_string()))?;
+    if stack.contains(&canonical) {
+        return Err(error("the file includes itself recursively".to_owned()));
+    }
+    let content = fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+
+    stack.push(canonical);
+    for token in tokenize(&content).map_err(error)? {
+        expand_arg(OsStr::new(&token), stack, out)?;
+    }
+    stack.pop();
+
+    Ok(())
+}
+
+/// Splits the content of a response file into arguments.
+fn tokenize(content: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut token: Option<String> = None;
+    let mut chars = content.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => tokens.extend(token.take()),
+            '\\' => {
+                let token = token.get_or_insert_with(String::new);
+                token.extend(chars.next());
+            }
+            '\'' => {
+                let token = token.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => token.push(c),
+                        None => return Err("unterminated single quote".to_owned()),
+                    }
+                }
+            }
+            '"' => {
+                let token = token.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => token.push(c),
+                            Some(c) => {
+                                token.push('\\');
+                                token.push(c);
+                            }
+                            None => return Err("unterminated double quote".to_owned()),
+                        },
+                        Some(c) => token.push(c),
+                        None => return Err("unterminated double quote".to_owned()),
+                    }
+                }
+            }
+            c => token.get_or_insert_with(String::new).push(c),
+        }
+    }
+    tokens.extend(token);
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(tokenize(""), Ok(vec![]));
+        assert_eq!(tokenize(" \n\t "), Ok(vec![]));
+        assert_eq!(
+            tokenize("-a  --foo=bar\nbaz\n"),
+            Ok(vec!["-a".into(), "--foo=bar".into(), "baz".into()])
+        );
+        assert_eq!(
+            tokenize(r#"'a b' "c \"d\" \e" f\ g '' x"y"z"#),
+            Ok(vec![
+                "a b".into(),
+                r#"c "d" \e"#.into(),
+                "f g".into(),
+                "".into(),
+                "xyz".into()
+            ])
+        );
+        assert_eq!(tokenize("'a"), Err("unterminated single quote".to_owned()));
+        assert_eq!(tokenize(r#"a "b\""#), Err("unterminated double quote".to_owned()));
+    }
+}
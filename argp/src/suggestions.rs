@@ -0,0 +1,84 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// SPDX-FileCopyrightText: 2023 Jakub Jirutka <jakub@jirutka.cz>
+
+//! This module provides "Did you mean ...?" suggestions for unknown options
+//! and subcommands. It's compiled only when the `suggestions` feature is
+//! enabled.
+
+use std::mem;
+
+/// Returns the candidate most similar to the given `arg`, or `None` if none of
+/// the candidates is similar enough.
+pub(crate) fn did_you_mean<'a>(
+    arg: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    candidates
+        .into_iter()
+        .map(|candidate| (distance(arg, candidate), candidate))
+        .filter(|(dist, candidate)| *dist <= (candidate.trim_start_matches('-').len() + 1) / 3)
+        .min_by_key(|(dist, _)| *dist)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Calculates the optimal string alignment distance between the given
+/// strings, i.e. the Levenshtein distance that also counts a transposition of
+/// two adjacent characters as a single edit.
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut prev2 = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        cur[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            cur[j] = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                cur[j] = cur[j].min(prev2[j - 2] + 1);
+            }
+        }
+        mem::swap(&mut prev2, &mut prev);
+        mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        for (a, b, expected) in [
+            ("", "", 0),
+            ("", "abc", 3),
+            ("status", "status", 0),
+            ("sttaus", "status", 1),
+            ("--hieght", "--height", 1),
+            ("--heigt", "--height", 1),
+            ("--weight", "--height", 1),
+            ("kitten", "sitting", 3),
+        ] {
+            assert_eq!(distance(a, b), expected, "{} -> {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        let candidates = ["--height", "--help", "--pilot-nickname"];
+
+        assert_eq!(did_you_mean("--hieght", candidates), Some("--height"));
+        assert_eq!(did_you_mean("--hepl", candidates), Some("--help"));
+        assert_eq!(did_you_mean("--pilot-nick", candidates), Some("--pilot-nickname"));
+        assert_eq!(did_you_mean("--pilot", candidates), None);
+        assert_eq!(did_you_mean("--foo", candidates), None);
+        assert_eq!(did_you_mean("sttaus", ["add", "status"]), Some("status"));
+        assert_eq!(did_you_mean("ad", ["add", "status"]), Some("add"));
+        assert_eq!(did_you_mean("--x", ["--a", "--b"]), None);
+    }
+}
@@ -11,11 +11,12 @@
     clippy::unwrap_in_result
 )]
 
-use std::ffi::OsStr;
+use std::ffi::{OsStr, OsString};
 use std::fmt::Debug;
 
 use argp::{
-    CommandInfo, DynamicSubCommand, EarlyExit, Error, FromArgs, HelpStyle, MissingRequirements,
+    CommandInfo, DynamicSubCommand, EarlyExit, Error, FromArgValue, FromArgs, HelpStyle,
+    MissingRequirements,
 };
 
 const EMPTY_ARGS: &[&OsStr] = &[];
@@ -195,20 +196,15 @@ fn dynamic_subcommand_example() {
 
     impl DynamicSubCommand for DynamicSubCommandImpl {
         fn commands() -> &'static [&'static CommandInfo] {
-            &[
-                &CommandInfo {
-                    name: "three",
-                    description: "Third command",
-                },
-                &CommandInfo {
-                    name: "four",
-                    description: "Fourth command",
-                },
+            static COMMANDS: &[&CommandInfo] = &[
+                &CommandInfo::new("three", "Third command"),
+                &CommandInfo::new("four", "Fourth command"),
                 &CommandInfo {
-                    name: "five",
-                    description: "Fifth command",
+                    hidden_aliases: &["5"],
+                    ..CommandInfo::new("five", "Fifth command")
                 },
-            ]
+            ];
+            COMMANDS
         }
 
         fn try_from_args(
@@ -314,6 +310,16 @@ fn dynamic_subcommand_example() {
             })
         },
     );
+
+    let five = TopLevel::from_args(&["cmdname"], &["5", "gold rings"]).expect("sc 5 alias");
+    assert_eq!(
+        five,
+        TopLevel {
+            nested: MySubCommandEnum::ThreeFourFive(DynamicSubCommandImpl {
+                got: "Fifth command got \"gold rings\"".to_owned()
+            })
+        },
+    );
 }
 
 #[test]
@@ -329,208 +335,3211 @@ fn multiline_doc_comment_description() {
         _s: bool,
     }
 
-    assert_help_string::<Cmd>(
-        r###"Usage: test_arg_0 [--s]
+    assert_help_string::<Cmd>(
+        r###"Usage: test_arg_0 [--s]
+
+Short description
+
+Options:
+      --s     a switch with a description that is spread across a number of
+              lines of comments.
+  -h, --help  Show this help message and exit.
+"###,
+    );
+}
+
+#[test]
+fn default_number() {
+    #[derive(FromArgs)]
+    /// Short description
+    struct Cmd {
+        #[argp(option, default = "5")]
+        /// fooey
+        x: u8,
+    }
+
+    let cmd = Cmd::from_args(&["cmdname"], EMPTY_ARGS).unwrap();
+    assert_eq!(cmd.x, 5);
+}
+
+#[test]
+fn default_function() {
+    const MSG: &str = "hey I just met you";
+    fn call_me_maybe() -> String {
+        MSG.to_owned()
+    }
+
+    #[derive(FromArgs)]
+    /// Short description
+    struct Cmd {
+        #[argp(option, default = "call_me_maybe()")]
+        /// fooey
+        msg: String,
+    }
+
+    let cmd = Cmd::from_args(&["cmdname"], EMPTY_ARGS).unwrap();
+    assert_eq!(cmd.msg, MSG);
+}
+
+#[test]
+fn missing_option_value() {
+    #[derive(FromArgs, Debug)]
+    /// Short description
+    struct Cmd {
+        #[argp(option)]
+        /// fooey
+        _msg: String,
+    }
+
+    let e = Cmd::from_args(&["cmdname"], &["--msg"])
+        .expect_err("Parsing missing option value should fail");
+    assert_eq!(e, EarlyExit::Err(Error::MissingArgValue("--msg".to_owned())));
+}
+
+fn assert_help_string<T: FromArgs>(help_str: &str) {
+    match T::from_args(&["test_arg_0"], &["--help"]) {
+        Ok(_) => panic!("help was parsed as args"),
+        Err(EarlyExit::Err(_) | EarlyExit::Version(_)) => panic!("expected EarlyExit::Help"),
+        Err(EarlyExit::Help(help)) => {
+            assert_eq!(help.generate(&FIXED_HELP_STYLE), help_str);
+        }
+    }
+}
+
+fn assert_output<T: FromArgs + Debug + PartialEq>(args: &[&str], expected: T) {
+    let t = T::from_args(&["cmd"], args).expect("failed to parse");
+    assert_eq!(t, expected);
+}
+
+fn assert_error<T: FromArgs + Debug>(args: &[&str], expected: Error) {
+    let e = T::from_args(&["cmd"], args).expect_err("unexpectedly succeeded parsing");
+    assert_eq!(EarlyExit::Err(expected), e);
+}
+
+fn missing_requirements(
+    positionals: &[&'static str],
+    options: &[&'static str],
+    subcommands: &[&'static str],
+) -> MissingRequirements {
+    let mut missing = MissingRequirements::default();
+
+    for pos in positionals {
+        missing.missing_positional_arg(pos);
+    }
+    for opt in options {
+        missing.missing_option(opt);
+    }
+    if !subcommands.is_empty() {
+        missing.missing_subcommands(subcommands.iter().copied());
+    }
+
+    missing
+}
+
+mod options {
+    use super::*;
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Parsed {
+        #[argp(option, short = 'n')]
+        /// fooey
+        n: usize,
+    }
+
+    #[test]
+    fn parsed() {
+        assert_output(&["-n", "5"], Parsed { n: 5 });
+        assert_error::<Parsed>(
+            &["-n", "x"],
+            Error::ParseArgument {
+                arg: "-n".to_owned(),
+                value: "x".into(),
+                msg: "invalid digit found in string".to_owned(),
+            },
+        );
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Repeating {
+        #[argp(option, short = 'n')]
+        /// fooey
+        n: Vec<String>,
+    }
+
+    #[test]
+    fn repeating() {
+        assert_help_string::<Repeating>(
+            r###"Usage: test_arg_0 [-n <n...>]
+
+Woot
+
+Options:
+  -n, --n <n>  fooey
+  -h, --help   Show this help message and exit.
+"###,
+        );
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct WithArgName {
+        #[argp(option, arg_name = "name")]
+        /// fooey
+        option_name: Option<String>,
+    }
+
+    #[test]
+    fn with_arg_name() {
+        assert_help_string::<WithArgName>(
+            r###"Usage: test_arg_0 [--option-name <name>]
+
+Woot
+
+Options:
+      --option-name <name>  fooey
+  -h, --help                Show this help message and exit.
+"###,
+        );
+    }
+
+    /// Woot
+    #[derive(FromArgs, Debug, PartialEq)]
+    struct ShortCombined {
+        /// fooey
+        #[argp(option, short = 'n')]
+        n: usize,
+        /// quiet
+        #[argp(switch, short = 'q')]
+        q: bool,
+        /// verbose
+        #[argp(switch, short = 'v')]
+        v: bool,
+    }
+
+    #[test]
+    fn short_combined() {
+        assert_output(
+            &["-qv", "-n", "5"],
+            ShortCombined {
+                n: 5,
+                q: true,
+                v: true,
+            },
+        );
+        assert_output(
+            &["-qvn", "5"],
+            ShortCombined {
+                n: 5,
+                q: true,
+                v: true,
+            },
+        );
+        assert_error::<ShortCombined>(
+            &["-nq", "5"],
+            Error::ParseArgument {
+                arg: "-n".to_owned(),
+                value: "q".into(),
+                msg: "invalid digit found in string".to_owned(),
+            },
+        );
+        assert_error::<ShortCombined>(&["-qn"], Error::MissingArgValue("-n".to_owned()));
+    }
+
+    #[test]
+    fn short_attached_value() {
+        assert_output(&["-n5"], Parsed { n: 5 });
+        assert_output(
+            &["-vn5"],
+            ShortCombined {
+                n: 5,
+                q: false,
+                v: true,
+            },
+        );
+        assert_output(
+            &["-qn55", "-v"],
+            ShortCombined {
+                n: 55,
+                q: true,
+                v: true,
+            },
+        );
+        assert_output(
+            &["-nqv"],
+            Repeating {
+                n: vec!["qv".into()],
+            },
+        );
+        assert_output(
+            &["-na=b", "-n=c=d"],
+            Repeating {
+                n: vec!["a=b".into(), "c=d".into()],
+            },
+        );
+        assert_error::<ShortCombined>(&["-n5", "-x"], Error::UnknownArgument("-x".into()));
+        assert_error::<ShortCombined>(&["-vxn5"], Error::UnknownArgument("-x".into()));
+    }
+
+    #[test]
+    fn attached_value() {
+        assert_output(&["--n=5"], Parsed { n: 5 });
+        assert_output(&["-n=5"], Parsed { n: 5 });
+        assert_output(
+            &["-qvn=5"],
+            ShortCombined {
+                n: 5,
+                q: true,
+                v: true,
+            },
+        );
+        assert_output(
+            &["--n=a=b"],
+            Repeating {
+                n: vec!["a=b".into()],
+            },
+        );
+        assert_output(&["--n="], Repeating { n: vec!["".into()] });
+        assert_error::<Parsed>(
+            &["--n="],
+            Error::ParseArgument {
+                arg: "--n".to_owned(),
+                value: "".into(),
+                msg: "cannot parse integer from empty string".to_owned(),
+            },
+        );
+        assert_error::<ShortCombined>(
+            &["-n", "5", "--q=yes"],
+            Error::UnexpectedArgValue("--q".to_owned()),
+        );
+        assert_error::<ShortCombined>(
+            &["-n", "5", "-q=yes"],
+            Error::UnexpectedArgValue("-q".to_owned()),
+        );
+        assert_error::<ShortCombined>(&["--nope=5"], Error::UnknownArgument("--nope".into()));
+    }
+}
+
+mod negatable {
+    use super::*;
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Negatable {
+        /// whether to use colors
+        #[argp(switch, negatable)]
+        color: Option<bool>,
+
+        /// whether to be verbose
+        #[argp(switch, short = 'v', negatable)]
+        verbose: bool,
+    }
+
+    #[test]
+    fn positive_and_negative() {
+        assert_output(
+            &[],
+            Negatable {
+                color: None,
+                verbose: false,
+            },
+        );
+        assert_output(
+            &["--color", "-v"],
+            Negatable {
+                color: Some(true),
+                verbose: true,
+            },
+        );
+        assert_output(
+            &["--no-color", "--no-verbose"],
+            Negatable {
+                color: Some(false),
+                verbose: false,
+            },
+        );
+    }
+
+    #[test]
+    fn last_occurrence_wins() {
+        assert_output(
+            &["--color", "--no-color", "--no-verbose", "--verbose"],
+            Negatable {
+                color: Some(false),
+                verbose: true,
+            },
+        );
+    }
+
+    #[test]
+    fn negated_value_not_allowed() {
+        assert_error::<Negatable>(
+            &["--no-color=yes"],
+            Error::UnexpectedArgValue("--no-color".to_owned()),
+        );
+    }
+
+    #[test]
+    fn help() {
+        assert_help_string::<Negatable>(
+            r###"Usage: test_arg_0 [--[no-]color] [-v]
+
+Woot
+
+Options:
+      --[no-]color    whether to use colors
+  -v, --[no-]verbose  whether to be verbose
+  -h, --help          Show this help message and exit.
+"###,
+        );
+    }
+}
+
+mod delimiter {
+    use super::*;
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Cmd {
+        /// tags
+        #[argp(option, short = 't', arg_name = "tag", delimiter = ',')]
+        tags: Vec<String>,
+
+        /// ports
+        #[argp(option, arg_name = "port", delimiter = ':')]
+        ports: Vec<u16>,
+    }
+
+    #[test]
+    fn split() {
+        assert_output(
+            &["--tags", "a,b", "-tc", "--ports=80:443", "--tags="],
+            Cmd {
+                tags: vec![
+                    "a".to_owned(),
+                    "b".to_owned(),
+                    "c".to_owned(),
+                    "".to_owned(),
+                ],
+                ports: vec![80, 443],
+            },
+        );
+    }
+
+    #[test]
+    fn escaped() {
+        assert_output(
+            &["--tags", r"a\,b,c\\,d\e"],
+            Cmd {
+                tags: vec![r"a,b".to_owned(), r"c\".to_owned(), r"d\e".to_owned()],
+                ports: vec![],
+            },
+        );
+    }
+
+    #[test]
+    fn invalid_item() {
+        assert_error::<Cmd>(
+            &["--ports", "80:http:443"],
+            Error::ParseArgument {
+                arg: "--ports".to_owned(),
+                value: "http".into(),
+                msg: "invalid digit found in string".to_owned(),
+            },
+        );
+    }
+
+    #[test]
+    fn help() {
+        assert_help_string::<Cmd>(
+            r###"Usage: test_arg_0 [-t <tag>[,<tag>...]] [--ports <port>[:<port>...]]
+
+Woot
+
+Options:
+  -t, --tags <tag>[,<tag>...]  tags
+      --ports <port>[:<port>...]
+                               ports
+  -h, --help                   Show this help message and exit.
+"###,
+        );
+    }
+}
+
+mod multiple_values {
+    use super::*;
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Cmd {
+        /// point
+        #[argp(option, short = 'p')]
+        point: Option<[f64; 3]>,
+
+        /// rename
+        #[argp(option, arg_name = "old new")]
+        rename: Vec<(String, String)>,
+
+        /// size
+        #[argp(option, arg_name = "n", default = "(1, 1)")]
+        size: (u8, u16),
+    }
+
+    #[test]
+    fn parse() {
+        assert_output(
+            &[
+                "-p",
+                "1",
+                "2.5",
+                "3",
+                "--rename",
+                "a",
+                "b",
+                "--rename=c",
+                "d",
+                "--size",
+                "2",
+                "300",
+            ],
+            Cmd {
+                point: Some([1.0, 2.5, 3.0]),
+                rename: vec![
+                    ("a".to_owned(), "b".to_owned()),
+                    ("c".to_owned(), "d".to_owned()),
+                ],
+                size: (2, 300),
+            },
+        );
+        assert_output(
+            &["-p0", "0", "0"],
+            Cmd {
+                point: Some([0.0, 0.0, 0.0]),
+                rename: vec![],
+                size: (1, 1),
+            },
+        );
+    }
+
+    #[test]
+    fn missing_values() {
+        assert_error::<Cmd>(
+            &["--rename", "a"],
+            Error::MissingArgValues {
+                arg: "--rename".to_owned(),
+                expected: 2,
+            },
+        );
+        assert_error::<Cmd>(
+            &["-p", "1", "2"],
+            Error::MissingArgValues {
+                arg: "-p".to_owned(),
+                expected: 3,
+            },
+        );
+    }
+
+    #[test]
+    fn invalid_value() {
+        assert_error::<Cmd>(
+            &["--size", "1", "x"],
+            Error::ParseArgument {
+                arg: "--size".to_owned(),
+                value: "x".into(),
+                msg: "invalid digit found in string".to_owned(),
+            },
+        );
+        assert_error::<Cmd>(
+            &["-p", "1", "2", "3", "-p", "4", "5", "6"],
+            Error::DuplicateOption("-p".to_owned()),
+        );
+    }
+
+    #[test]
+    fn single_element() {
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Woot
+        struct Cmd {
+            /// name
+            #[argp(option)]
+            name: Option<(String,)>,
+
+            /// tag
+            #[argp(option, short = 't')]
+            tag: Vec<[String; 1]>,
+        }
+
+        assert_output(
+            &["--name", "a b", "-t", "c d", "-te f"],
+            Cmd {
+                name: Some(("a b".to_owned(),)),
+                tag: vec![["c d".to_owned()], ["e f".to_owned()]],
+            },
+        );
+    }
+
+    #[test]
+    fn help() {
+        assert_help_string::<Cmd>(
+            r###"Usage: test_arg_0 [-p <point> <point> <point>] [--rename <old> <new>...]
+                  [--size <n> <n>]
+
+Woot
+
+Options:
+  -p, --point <point> <point> <point>
+                            point
+      --rename <old> <new>  rename
+      --size <n> <n>        size
+  -h, --help                Show this help message and exit.
+"###,
+        );
+    }
+}
+
+mod default_missing {
+    use super::*;
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Cmd {
+        /// color
+        #[argp(
+            option,
+            arg_name = "when",
+            default_missing = "auto",
+            default = "String::from(\"never\")"
+        )]
+        color: String,
+
+        /// log
+        #[argp(option, short = 'l', default_missing = "-")]
+        log: Option<String>,
+
+        /// level
+        #[argp(option, default_missing = "1")]
+        level: Vec<u8>,
+
+        /// verbose
+        #[argp(switch, short = 'v')]
+        verbose: bool,
+
+        #[argp(positional)]
+        /// input
+        input: Option<String>,
+    }
+
+    #[test]
+    fn bare() {
+        assert_output(
+            &["--color", "-l", "--level", "--level"],
+            Cmd {
+                color: "auto".to_owned(),
+                log: Some("-".to_owned()),
+                level: vec![1, 1],
+                verbose: false,
+                input: None,
+            },
+        );
+    }
+
+    #[test]
+    fn attached() {
+        assert_output(
+            &["--color=always", "-l=out.log", "--level=2"],
+            Cmd {
+                color: "always".to_owned(),
+                log: Some("out.log".to_owned()),
+                level: vec![2],
+                verbose: false,
+                input: None,
+            },
+        );
+    }
+
+    #[test]
+    fn short_cluster() {
+        assert_output(
+            &["-lv"],
+            Cmd {
+                color: "never".to_owned(),
+                log: Some("-".to_owned()),
+                level: vec![],
+                verbose: true,
+                input: None,
+            },
+        );
+        assert_output(
+            &["-vl=out.log"],
+            Cmd {
+                color: "never".to_owned(),
+                log: Some("out.log".to_owned()),
+                level: vec![],
+                verbose: true,
+                input: None,
+            },
+        );
+    }
+
+    #[test]
+    fn next_arg_not_consumed() {
+        assert_output(
+            &["--color", "always"],
+            Cmd {
+                color: "auto".to_owned(),
+                log: None,
+                level: vec![],
+                verbose: false,
+                input: Some("always".to_owned()),
+            },
+        );
+    }
+
+    #[test]
+    fn help() {
+        assert_help_string::<Cmd>(
+            r###"Usage: test_arg_0 [--color[=<when>]] [-l[=<log>]] [--level[=<level>]...] [-v]
+                  [<input>]
+
+Woot
+
+Arguments:
+  input                  input
+
+Options:
+      --color[=<when>]   color
+  -l, --log[=<log>]      log
+      --level[=<level>]  level
+  -v, --verbose          verbose
+  -h, --help             Show this help message and exit.
+"###,
+        );
+    }
+}
+
+mod conflicts {
+    use super::*;
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    #[argp(group(name = "format", required))]
+    #[argp(group(name = "filter", multiple = true))]
+    struct Cmd {
+        /// json
+        #[argp(switch, group = "format")]
+        json: bool,
+
+        /// yaml
+        #[argp(switch, group = "format", env = "ARGP_TEST_CONFLICTS_YAML")]
+        yaml: bool,
+
+        /// table
+        #[argp(option, group = "format", arg_name = "columns")]
+        table: Option<String>,
+
+        /// all
+        #[argp(switch, short = 'a', conflicts_with = "package")]
+        all: bool,
+
+        /// package
+        #[argp(option, short = 'p', group = "filter")]
+        package: Vec<String>,
+
+        /// exclude
+        #[argp(option, group = "filter")]
+        exclude: Option<String>,
+    }
+
+    #[test]
+    fn no_conflicts() {
+        assert_output(
+            &["--json", "-p", "foo", "--exclude", "bar"],
+            Cmd {
+                json: true,
+                yaml: false,
+                table: None,
+                all: false,
+                package: vec!["foo".to_owned()],
+                exclude: Some("bar".to_owned()),
+            },
+        );
+        assert_output(
+            &["--table=name", "--all"],
+            Cmd {
+                json: false,
+                yaml: false,
+                table: Some("name".to_owned()),
+                all: true,
+                package: vec![],
+                exclude: None,
+            },
+        );
+    }
+
+    #[test]
+    fn conflicts_with() {
+        assert_error::<Cmd>(
+            &["--json", "-p", "foo", "-a"],
+            Error::ConflictingOptions {
+                arg: "--all".to_owned(),
+                other: "--package".to_owned(),
+            },
+        );
+    }
+
+    #[test]
+    fn group_not_multiple() {
+        assert_error::<Cmd>(
+            &["--table", "name", "--json"],
+            Error::ConflictingOptions {
+                arg: "--json".to_owned(),
+                other: "--table".to_owned(),
+            },
+        );
+    }
+
+    #[test]
+    fn group_required() {
+        let mut missing = MissingRequirements::default();
+        missing.missing_group(&["--json", "--yaml", "--table"]);
+
+        let e = Cmd::from_args(&["cmd"], &["--all"]).expect_err("Parsing should fail");
+        assert_eq!(e, EarlyExit::Err(Error::MissingRequirements(missing)));
+        assert_eq!(
+            e.to_string(),
+            "One of the following options must be provided:\n    --json\n    --yaml\n    --table\n"
+        );
+    }
+
+    #[test]
+    fn group_required_from_env() {
+        std::env::set_var("ARGP_TEST_CONFLICTS_YAML", "1");
+        let res = Cmd::from_args(&["cmd"], EMPTY_ARGS);
+        std::env::remove_var("ARGP_TEST_CONFLICTS_YAML");
+
+        assert!(res.unwrap().yaml);
+    }
+
+    #[test]
+    fn env_does_not_conflict_with_command_line() {
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Woot
+        #[argp(group(name = "format"))]
+        struct Cmd {
+            /// json
+            #[argp(switch, group = "format", env = "ARGP_TEST_CONFLICTS_ENV_JSON")]
+            json: bool,
+
+            /// yaml
+            #[argp(switch, group = "format")]
+            yaml: bool,
+
+            /// all
+            #[argp(switch, env = "ARGP_TEST_CONFLICTS_ENV_ALL", conflicts_with = "yaml")]
+            all: bool,
+        }
+
+        std::env::set_var("ARGP_TEST_CONFLICTS_ENV_JSON", "1");
+        std::env::set_var("ARGP_TEST_CONFLICTS_ENV_ALL", "1");
+        let res = Cmd::from_args(&["cmd"], &["--yaml"]);
+        let res_env_only = Cmd::from_args(&["cmd"], EMPTY_ARGS);
+        std::env::remove_var("ARGP_TEST_CONFLICTS_ENV_JSON");
+        std::env::remove_var("ARGP_TEST_CONFLICTS_ENV_ALL");
+
+        assert_eq!(
+            res.unwrap(),
+            Cmd {
+                json: false,
+                yaml: true,
+                all: false,
+            }
+        );
+        assert_eq!(
+            res_env_only.unwrap(),
+            Cmd {
+                json: true,
+                yaml: false,
+                all: true,
+            }
+        );
+    }
+
+    #[test]
+    fn help() {
+        assert_help_string::<Cmd>(
+            r###"Usage: test_arg_0 (--json | --yaml | --table <columns>) [-a]
+                  [-p <package...> | --exclude <exclude>]
+
+Woot
+
+Options:
+      --json               json
+      --yaml               yaml [env: ARGP_TEST_CONFLICTS_YAML]
+      --table <columns>    table
+  -a, --all                all
+  -p, --package <package>  package
+      --exclude <exclude>  exclude
+  -h, --help               Show this help message and exit.
+"###,
+        );
+    }
+}
+
+mod requirements {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Auth {
+        None,
+        Token,
+    }
+
+    impl FromArgValue for Auth {
+        fn from_arg_value(value: &OsStr) -> Result<Self, String> {
+            match value.to_str() {
+                Some("none") => Ok(Self::None),
+                Some("token") => Ok(Self::Token),
+                _ => Err("expected none or token".to_owned()),
+            }
+        }
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Cmd {
+        /// tls key
+        #[argp(option, requires = "tls_cert")]
+        tls_key: Option<String>,
+
+        /// tls cert
+        #[argp(option)]
+        tls_cert: Option<String>,
+
+        /// output
+        #[argp(option, required_unless = "dry_run", required_unless = "stdout")]
+        output: Option<String>,
+
+        /// dry run
+        #[argp(switch)]
+        dry_run: bool,
+
+        /// stdout
+        #[argp(switch)]
+        stdout: bool,
+
+        /// auth
+        #[argp(option, default = "Auth::None")]
+        auth: Auth,
+
+        /// token
+        #[argp(option, required_if = "auth=token")]
+        token: Option<String>,
+    }
+
+    fn cmd() -> Cmd {
+        Cmd {
+            tls_key: None,
+            tls_cert: None,
+            output: None,
+            dry_run: false,
+            stdout: false,
+            auth: Auth::None,
+            token: None,
+        }
+    }
+
+    fn missing(options: &[(&'static str, &'static str)]) -> Error {
+        let mut missing = MissingRequirements::default();
+        for (name, condition) in options {
+            missing.missing_option_if(name, condition);
+        }
+        Error::MissingRequirements(missing)
+    }
+
+    #[test]
+    fn satisfied() {
+        assert_output(
+            &["--tls-key", "k", "--tls-cert", "c", "--output", "o"],
+            Cmd {
+                tls_key: Some("k".to_owned()),
+                tls_cert: Some("c".to_owned()),
+                output: Some("o".to_owned()),
+                ..cmd()
+            },
+        );
+        assert_output(
+            &["--dry-run", "--auth", "token", "--token", "t"],
+            Cmd {
+                dry_run: true,
+                auth: Auth::Token,
+                token: Some("t".to_owned()),
+                ..cmd()
+            },
+        );
+        assert_output(
+            &["--stdout"],
+            Cmd {
+                stdout: true,
+                ..cmd()
+            },
+        );
+    }
+
+    #[test]
+    fn requires() {
+        assert_error::<Cmd>(
+            &["--dry-run", "--tls-key", "k"],
+            missing(&[("--tls-cert", "required by --tls-key")]),
+        );
+    }
+
+    #[test]
+    fn required_unless() {
+        assert_error::<Cmd>(
+            &[],
+            missing(&[("--output", "required unless --dry-run or --stdout is provided")]),
+        );
+    }
+
+    #[test]
+    fn required_if() {
+        assert_error::<Cmd>(
+            &["--dry-run", "--auth=token"],
+            missing(&[("--token", "required if --auth is 'token'")]),
+        );
+    }
+
+    #[test]
+    fn required_if_unparsable_value() {
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Woot
+        struct Cmd {
+            /// port
+            #[argp(option)]
+            port: Option<u16>,
+
+            /// host
+            #[argp(option, required_if = "port=http")]
+            host: Option<String>,
+        }
+
+        // A value that cannot be parsed never matches.
+        assert_output(
+            &["--port", "80"],
+            Cmd {
+                port: Some(80),
+                host: None,
+            },
+        );
+    }
+
+    #[test]
+    fn error_message() {
+        let e = Cmd::from_args(&["cmd"], &["--tls-key", "k", "--auth", "token"])
+            .expect_err("Parsing should fail");
+        assert_eq!(
+            e.to_string(),
+            "Required options not provided:
+    --tls-cert (required by --tls-key)
+    --output (required unless --dry-run or --stdout is provided)
+    --token (required if --auth is 'token')
+"
+        );
+    }
+}
+
+mod value_validation {
+    use super::*;
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Cmd {
+        /// jobs
+        #[argp(option, short = 'j', range = "1..=256")]
+        jobs: Option<u16>,
+
+        /// level
+        #[argp(option, possible_values = ["debug", "info"], default = "String::from(\"info\")")]
+        level: String,
+
+        /// ratio
+        #[argp(option, range = "0.0..1.0", delimiter = ',')]
+        ratio: Vec<f64>,
+
+        #[argp(positional, range = "1..")]
+        /// count
+        count: Option<i32>,
+    }
+
+    #[test]
+    fn valid() {
+        assert_output(
+            &["-j256", "--level=debug", "--ratio", "0,0.5", "1"],
+            Cmd {
+                jobs: Some(256),
+                level: "debug".to_owned(),
+                ratio: vec![0.0, 0.5],
+                count: Some(1),
+            },
+        );
+    }
+
+    #[test]
+    fn out_of_range() {
+        assert_error::<Cmd>(
+            &["-j", "0"],
+            Error::ParseArgument {
+                arg: "-j".to_owned(),
+                value: "0".into(),
+                msg: "must be in the range 1..=256".to_owned(),
+            },
+        );
+        assert_error::<Cmd>(
+            &["--ratio", "0.5,1.0"],
+            Error::ParseArgument {
+                arg: "--ratio".to_owned(),
+                value: "1.0".into(),
+                msg: "must be in the range 0.0..1.0".to_owned(),
+            },
+        );
+        assert_error::<Cmd>(
+            &["0"],
+            Error::ParseArgument {
+                arg: "count".to_owned(),
+                value: "0".into(),
+                msg: "must be in the range 1..".to_owned(),
+            },
+        );
+    }
+
+    #[test]
+    fn invalid_value_before_range() {
+        assert_error::<Cmd>(
+            &["--jobs", "x"],
+            Error::ParseArgument {
+                arg: "--jobs".to_owned(),
+                value: "x".into(),
+                msg: "invalid digit found in string".to_owned(),
+            },
+        );
+    }
+
+    #[test]
+    fn not_possible_value() {
+        assert_error::<Cmd>(
+            &["--level", "trace"],
+            Error::ParseArgument {
+                arg: "--level".to_owned(),
+                value: "trace".into(),
+                msg: "must be one of: debug, info".to_owned(),
+            },
+        );
+    }
+
+    #[test]
+    fn help() {
+        assert_help_string::<Cmd>(
+            r###"Usage: test_arg_0 [-j <jobs>] [--level <level>] [--ratio <ratio>[,<ratio>...]]
+                  [<count>]
+
+Woot
+
+Arguments:
+  count                count [1..]
+
+Options:
+  -j, --jobs <jobs>    jobs [1..=256]
+      --level <level>  level [possible values: debug, info]
+      --ratio <ratio>[,<ratio>...]
+                       ratio [0.0..1.0]
+  -h, --help           Show this help message and exit.
+"###,
+        );
+    }
+}
+
+mod derive_from_arg_value {
+    use super::*;
+
+    #[derive(FromArgValue, Debug, PartialEq)]
+    enum Level {
+        Debug,
+        #[argp(alias = "information")]
+        Info,
+        #[argp(name = "warn")]
+        Warning,
+        DryRun,
+    }
+
+    #[derive(FromArgValue, Debug, PartialEq)]
+    #[argp(case_insensitive)]
+    enum Color {
+        Auto,
+        #[argp(alias = "yes")]
+        Always,
+    }
+
+    #[derive(FromArgValue, Debug, PartialEq)]
+    struct Jobs(u16);
+
+    #[derive(FromArgValue, Debug, PartialEq)]
+    struct Wrapped {
+        level: Level,
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Cmd {
+        /// level
+        #[argp(option, default = "Level::Info")]
+        level: Level,
+
+        /// color
+        #[argp(option)]
+        color: Option<Color>,
+
+        /// jobs
+        #[argp(option)]
+        jobs: Option<Jobs>,
+
+        #[argp(positional)]
+        /// wrapped
+        wrapped: Vec<Wrapped>,
+    }
+
+    #[test]
+    fn enum_values() {
+        assert_eq!(Level::from_arg_value("debug".as_ref()), Ok(Level::Debug));
+        assert_eq!(Level::from_arg_value("information".as_ref()), Ok(Level::Info));
+        assert_eq!(Level::from_arg_value("warn".as_ref()), Ok(Level::Warning));
+        assert_eq!(Level::from_arg_value("dry-run".as_ref()), Ok(Level::DryRun));
+        assert_eq!(
+            Level::from_arg_value("Debug".as_ref()),
+            Err("must be one of: debug, info, warn, dry-run".to_owned())
+        );
+        assert_eq!(Level::POSSIBLE_VALUES, ["debug", "info", "warn", "dry-run"]);
+    }
+
+    #[test]
+    fn case_insensitive() {
+        assert_eq!(Color::from_arg_value("AUTO".as_ref()), Ok(Color::Auto));
+        assert_eq!(Color::from_arg_value("Yes".as_ref()), Ok(Color::Always));
+    }
+
+    #[test]
+    fn newtype() {
+        assert_eq!(Jobs::from_arg_value("4".as_ref()), Ok(Jobs(4)));
+        assert_eq!(
+            Wrapped::from_arg_value("debug".as_ref()),
+            Ok(Wrapped {
+                level: Level::Debug
+            })
+        );
+        assert_eq!(Wrapped::POSSIBLE_VALUES, Level::POSSIBLE_VALUES);
+        assert_eq!(Jobs::POSSIBLE_VALUES, [] as [&str; 0]);
+    }
+
+    #[test]
+    fn parse() {
+        assert_output(
+            &["--color", "always", "--jobs", "2", "warn", "info"],
+            Cmd {
+                level: Level::Info,
+                color: Some(Color::Always),
+                jobs: Some(Jobs(2)),
+                wrapped: vec![
+                    Wrapped {
+                        level: Level::Warning,
+                    },
+                    Wrapped { level: Level::Info },
+                ],
+            },
+        );
+        assert_error::<Cmd>(
+            &["--level", "trace"],
+            Error::ParseArgument {
+                arg: "--level".to_owned(),
+                value: "trace".into(),
+                msg: "must be one of: debug, info, warn, dry-run".to_owned(),
+            },
+        );
+    }
+
+    #[test]
+    fn help() {
+        assert_help_string::<Cmd>(
+            r###"Usage: test_arg_0 [--level <level>] [--color <color>] [--jobs <jobs>]
+                  [<wrapped...>]
+
+Woot
+
+Arguments:
+  wrapped              wrapped [possible values: debug, info, warn, dry-run]
+
+Options:
+      --level <level>  level [possible values: debug, info, warn, dry-run]
+      --color <color>  color [possible values: auto, always]
+      --jobs <jobs>    jobs
+  -h, --help           Show this help message and exit.
+"###,
+        );
+    }
+}
+
+mod aliases {
+    use super::*;
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Top level.
+    #[argp(abbreviations)]
+    struct TopLevel {
+        #[argp(option, alias = "--colour", hidden_alias = "-C")]
+        /// When to use colors.
+        color: Option<String>,
+
+        #[argp(switch, short = 'v', global, aliases = ["--verb", "--talkative"])]
+        /// Be verbose.
+        verbose: bool,
+
+        #[argp(subcommand)]
+        nested: Option<SubCommandEnum>,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    #[argp(subcommand)]
+    enum SubCommandEnum {
+        Remove(SubCommandRemove),
+        List(SubCommandList),
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Remove a package.
+    #[argp(subcommand, name = "remove", alias = "rm", hidden_alias = "del")]
+    struct SubCommandRemove {
+        #[argp(positional)]
+        /// Name of the package.
+        name: String,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// List packages.
+    #[argp(subcommand, name = "list")]
+    struct SubCommandList {}
+
+    #[test]
+    fn option_aliases() {
+        assert_output(
+            &["--colour", "never", "--talkative"],
+            TopLevel {
+                color: Some("never".into()),
+                verbose: true,
+                nested: None,
+            },
+        );
+        assert_output(
+            &["-C", "always", "--verb"],
+            TopLevel {
+                color: Some("always".into()),
+                verbose: true,
+                nested: None,
+            },
+        );
+        assert_error::<TopLevel>(
+            &["--color", "never", "--colour", "always"],
+            Error::DuplicateOption("--colour".into()),
+        );
+    }
+
+    #[test]
+    fn abbreviation_of_option_and_alias_is_not_ambiguous() {
+        assert_output(
+            &["--col", "never"],
+            TopLevel {
+                color: Some("never".into()),
+                verbose: false,
+                nested: None,
+            },
+        );
+    }
+
+    #[test]
+    fn subcommand_aliases() {
+        let expected = TopLevel {
+            color: None,
+            verbose: true,
+            nested: Some(SubCommandEnum::Remove(SubCommandRemove { name: "foo".into() })),
+        };
+        assert_output(&["rm", "--talkative", "foo"], expected);
+        assert_output(
+            &["del", "foo"],
+            TopLevel {
+                color: None,
+                verbose: false,
+                nested: Some(SubCommandEnum::Remove(SubCommandRemove { name: "foo".into() })),
+            },
+        );
+    }
+
+    #[test]
+    fn help() {
+        assert_help_string::<TopLevel>(
+            r###"Usage: test_arg_0 [--color <color>] [-v] [<command>] [<args>]
+
+Top level.
+
+Options:
+      --color <color>  When to use colors. [aliases: --colour]
+  -v, --verbose        Be verbose. [aliases: --verb, --talkative]
+  -h, --help           Show this help message and exit.
+
+Commands:
+  remove, rm           Remove a package.
+  list                 List packages.
+"###,
+        );
+    }
+
+    #[test]
+    fn subcommand_help_uses_canonical_name() {
+        let early_exit = TopLevel::from_args(&["cmd"], &["rm", "--help"]).unwrap_err();
+        match early_exit {
+            EarlyExit::Help(help) => {
+                assert!(help.generate_default().starts_with("Usage: cmd remove "));
+            }
+            _ => panic!("expected help"),
+        }
+    }
+}
+
+mod overridable {
+    use super::*;
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Top level.
+    struct TopLevel {
+        #[argp(option, global, overridable)]
+        /// Output format.
+        format: Option<String>,
+
+        #[argp(option)]
+        /// A name.
+        name: Option<String>,
+
+        #[argp(subcommand)]
+        nested: Option<SubCommandEnum>,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    #[argp(subcommand)]
+    enum SubCommandEnum {
+        One(SubCommandOne),
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// First subcommand.
+    #[argp(subcommand, name = "one", overridable)]
+    struct SubCommandOne {
+        #[argp(option, default = "1")]
+        /// Number of jobs.
+        jobs: u8,
+
+        #[argp(option)]
+        /// A pair.
+        pair: Option<(u8, u8)>,
+
+        #[argp(switch, negatable)]
+        /// Use colors.
+        color: Option<bool>,
+    }
+
+    #[test]
+    fn last_occurrence_wins() {
+        assert_output(
+            &["--format=json", "--format", "table"],
+            TopLevel {
+                format: Some("table".into()),
+                name: None,
+                nested: None,
+            },
+        );
+        assert_output(
+            &[
+                "one", "--jobs", "2", "--pair", "1", "2", "--jobs=4", "--pair", "3", "4",
+            ],
+            TopLevel {
+                format: None,
+                name: None,
+                nested: Some(SubCommandEnum::One(SubCommandOne {
+                    jobs: 4,
+                    pair: Some((3, 4)),
+                    color: None,
+                })),
+            },
+        );
+        assert_output(
+            &["one", "--color", "--no-color"],
+            TopLevel {
+                format: None,
+                name: None,
+                nested: Some(SubCommandEnum::One(SubCommandOne {
+                    jobs: 1,
+                    pair: None,
+                    color: Some(false),
+                })),
+            },
+        );
+    }
+
+    #[test]
+    fn global_option() {
+        assert_output(
+            &["--format", "json", "one", "--format", "table"],
+            TopLevel {
+                format: Some("table".into()),
+                name: None,
+                nested: Some(SubCommandEnum::One(SubCommandOne {
+                    jobs: 1,
+                    pair: None,
+                    color: None,
+                })),
+            },
+        );
+    }
+
+    #[test]
+    fn strict_by_default() {
+        assert_error::<TopLevel>(
+            &["--name", "a", "--name", "b"],
+            Error::DuplicateOption("--name".into()),
+        );
+    }
+
+    #[test]
+    fn invalid_value_is_reported() {
+        assert_error::<TopLevel>(
+            &["one", "--jobs", "2", "--jobs", "x"],
+            Error::ParseArgument {
+                arg: "--jobs".into(),
+                value: "x".into(),
+                msg: "invalid digit found in string".into(),
+            },
+        );
+    }
+}
+
+mod flatten {
+    use super::*;
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Logging options.
+    #[argp(flatten)]
+    struct LogOptions {
+        #[argp(switch, short = 'v', global)]
+        /// Be verbose.
+        verbose: bool,
+
+        #[argp(option)]
+        /// Path of the log file.
+        log_file: Option<String>,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Connection options.
+    struct ConnOptions {
+        #[argp(option, default = "22")]
+        /// Port to connect to.
+        port: u16,
+
+        #[argp(positional)]
+        /// Host to connect to.
+        host: String,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Top level.
+    struct TopLevel {
+        #[argp(option)]
+        /// Output format.
+        format: Option<String>,
+
+        #[argp(flatten)]
+        log: LogOptions,
+
+        #[argp(switch)]
+        /// Do not change anything.
+        dry_run: bool,
+
+        #[argp(subcommand)]
+        nested: Option<SubCommandEnum>,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    #[argp(subcommand)]
+    enum SubCommandEnum {
+        Connect(SubCommandConnect),
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Connect to a host.
+    #[argp(subcommand, name = "connect")]
+    struct SubCommandConnect {
+        #[argp(positional)]
+        /// Name of the user.
+        user: String,
+
+        #[argp(flatten)]
+        conn: ConnOptions,
+
+        #[argp(positional)]
+        /// Command to execute.
+        command: Option<String>,
+    }
+
+    #[test]
+    fn options_are_merged() {
+        assert_output(
+            &[
+                "--log-file",
+                "out.log",
+                "--dry-run",
+                "-v",
+                "--format",
+                "json",
+            ],
+            TopLevel {
+                format: Some("json".into()),
+                log: LogOptions {
+                    verbose: true,
+                    log_file: Some("out.log".into()),
+                },
+                dry_run: true,
+                nested: None,
+            },
+        );
+    }
+
+    #[test]
+    fn global_option_of_flattened_struct() {
+        assert_output(
+            &["connect", "-v", "root", "example.org"],
+            TopLevel {
+                format: None,
+                log: LogOptions {
+                    verbose: true,
+                    log_file: None,
+                },
+                dry_run: false,
+                nested: Some(SubCommandEnum::Connect(SubCommandConnect {
+                    user: "root".into(),
+                    conn: ConnOptions {
+                        port: 22,
+                        host: "example.org".into(),
+                    },
+                    command: None,
+                })),
+            },
+        );
+    }
+
+    #[test]
+    fn positionals_in_field_order() {
+        assert_output(
+            &["connect", "root", "--port", "2222", "example.org", "uptime"],
+            TopLevel {
+                format: None,
+                log: LogOptions {
+                    verbose: false,
+                    log_file: None,
+                },
+                dry_run: false,
+                nested: Some(SubCommandEnum::Connect(SubCommandConnect {
+                    user: "root".into(),
+                    conn: ConnOptions {
+                        port: 2222,
+                        host: "example.org".into(),
+                    },
+                    command: Some("uptime".into()),
+                })),
+            },
+        );
+    }
+
+    #[test]
+    fn missing_requirements_of_flattened_struct() {
+        assert_error::<TopLevel>(
+            &["connect", "root"],
+            Error::MissingRequirements(missing_requirements(&["host"], &[], &[])),
+        );
+    }
+
+    #[test]
+    fn duplicate_option_across_structs() {
+        assert_error::<TopLevel>(
+            &["--log-file", "a.log", "--log-file", "b.log"],
+            Error::DuplicateOption("--log-file".into()),
+        );
+    }
+
+    #[test]
+    fn help() {
+        assert_help_string::<TopLevel>(
+            r###"Usage: test_arg_0 [--format <format>] [-v] [--log-file <log-file>] [--dry-run]
+                  [<command>] [<args>]
+
+Top level.
+
+Options:
+      --format <format>      Output format.
+  -v, --verbose              Be verbose.
+      --log-file <log_file>  Path of the log file.
+      --dry-run              Do not change anything.
+  -h, --help                 Show this help message and exit.
+
+Commands:
+  connect                    Connect to a host.
+"###,
+        );
+    }
+
+    #[test]
+    fn subcommand_help() {
+        let early_exit = TopLevel::from_args(&["cmd"], &["connect", "--help"]).unwrap_err();
+        match early_exit {
+            EarlyExit::Help(help) => assert_eq!(
+                help.generate(&FIXED_HELP_STYLE),
+                r###"Usage: cmd connect [-v] [--port <port>] <user> <host> [<command>]
+
+Connect to a host.
+
+Arguments:
+  user               Name of the user.
+  host               Host to connect to.
+  command            Command to execute.
+
+Options:
+  -v, --verbose      Be verbose.
+      --port <port>  Port to connect to.
+  -h, --help         Show this help message and exit.
+"###
+            ),
+            _ => panic!("expected help"),
+        }
+    }
+}
+
+mod inline_subcommands {
+    use super::*;
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Top level.
+    struct TopLevel {
+        #[argp(switch, global)]
+        /// Be verbose.
+        verbose: bool,
+
+        #[argp(subcommand)]
+        nested: SubCommandEnum,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    #[argp(subcommand)]
+    enum SubCommandEnum {
+        /// Add a package.
+        Add {
+            #[argp(positional)]
+            /// Name of the package.
+            name: String,
+
+            #[argp(option, short = 'r')]
+            /// Repository to install from.
+            repo: Option<String>,
+        },
+
+        /// List packages.
+        #[argp(alias = "ls")]
+        List,
+
+        /// Remove unused packages.
+        AutoRemove {},
+
+        /// Show information about a package.
+        #[argp(name = "info")]
+        Show {
+            #[argp(positional)]
+            /// Name of the package.
+            name: String,
+        },
+
+        Other(SubCommandOther),
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Do something else.
+    #[argp(subcommand, name = "other")]
+    struct SubCommandOther {}
+
+    #[test]
+    fn struct_variant() {
+        assert_output(
+            &["add", "-r", "main", "foo"],
+            TopLevel {
+                verbose: false,
+                nested: SubCommandEnum::Add {
+                    name: "foo".into(),
+                    repo: Some("main".into()),
+                },
+            },
+        );
+        assert_output(
+            &["info", "--verbose", "foo"],
+            TopLevel {
+                verbose: true,
+                nested: SubCommandEnum::Show { name: "foo".into() },
+            },
+        );
+        assert_error::<TopLevel>(
+            &["add"],
+            Error::MissingRequirements(missing_requirements(&["name"], &[], &[])),
+        );
+    }
+
+    #[test]
+    fn unit_variant() {
+        assert_output(
+            &["ls"],
+            TopLevel {
+                verbose: false,
+                nested: SubCommandEnum::List,
+            },
+        );
+        assert_output(
+            &["auto-remove"],
+            TopLevel {
+                verbose: false,
+                nested: SubCommandEnum::AutoRemove {},
+            },
+        );
+        assert_error::<TopLevel>(&["list", "foo"], Error::UnknownArgument("foo".into()));
+    }
+
+    #[test]
+    fn mixed_with_single_field_variant() {
+        assert_output(
+            &["other"],
+            TopLevel {
+                verbose: false,
+                nested: SubCommandEnum::Other(SubCommandOther {}),
+            },
+        );
+    }
+
+    #[test]
+    fn no_name_collisions() {
+        #[derive(FromArgs, PartialEq, Debug)]
+        #[argp(subcommand)]
+        enum A {
+            /// Bc.
+            #[argp(name = "bc")]
+            BC,
+        }
+
+        #[derive(FromArgs, PartialEq, Debug)]
+        #[argp(subcommand)]
+        enum AB {
+            /// C.
+            C,
+        }
+
+        #[derive(FromArgs, PartialEq, Debug)]
+        /// Top level.
+        struct Cmd {
+            #[argp(subcommand)]
+            a: A,
+        }
+
+        #[derive(FromArgs, PartialEq, Debug)]
+        /// Top level.
+        struct OtherCmd {
+            #[argp(subcommand)]
+            ab: AB,
+        }
+
+        assert_output(&["bc"], Cmd { a: A::BC });
+        assert_output(&["c"], OtherCmd { ab: AB::C });
+    }
+
+    #[test]
+    fn help() {
+        assert_help_string::<TopLevel>(
+            r###"Usage: test_arg_0 [--verbose] <command> [<args>]
+
+Top level.
+
+Options:
+      --verbose  Be verbose.
+  -h, --help     Show this help message and exit.
+
+Commands:
+  add            Add a package.
+  list, ls       List packages.
+  auto-remove    Remove unused packages.
+  info           Show information about a package.
+  other          Do something else.
+"###,
+        );
+    }
+
+    #[test]
+    fn subcommand_help() {
+        let early_exit = TopLevel::from_args(&["cmd"], &["add", "--help"]).unwrap_err();
+        match early_exit {
+            EarlyExit::Help(help) => assert_eq!(
+                help.generate(&FIXED_HELP_STYLE),
+                r###"Usage: cmd add [--verbose] [-r <repo>] <name>
+
+Add a package.
+
+Arguments:
+  name               Name of the package.
+
+Options:
+      --verbose      Be verbose.
+  -r, --repo <repo>  Repository to install from.
+  -h, --help         Show this help message and exit.
+"###
+            ),
+            _ => panic!("expected help"),
+        }
+    }
+}
+
+mod generic_subcommands {
+    use super::*;
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Shared front-end.
+    struct Cli<S> {
+        #[argp(switch, short = 'v', global)]
+        /// Be verbose.
+        verbose: bool,
+
+        #[argp(subcommand)]
+        cmd: S,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    #[argp(subcommand)]
+    enum ServerCmd<T> {
+        Start(SubCommandStart<T>),
+        Stop(SubCommandStop),
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Start the server.
+    #[argp(subcommand, name = "start")]
+    struct SubCommandStart<T> {
+        #[argp(option)]
+        /// Port to listen on.
+        port: Option<T>,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Stop the server.
+    #[argp(subcommand, name = "stop")]
+    struct SubCommandStop {}
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    #[argp(subcommand)]
+    enum ClientCmd {
+        Connect(SubCommandConnect),
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Connect to the server.
+    #[argp(subcommand, name = "connect")]
+    struct SubCommandConnect {
+        #[argp(positional)]
+        /// Address of the server.
+        addr: String,
+    }
+
+    #[test]
+    fn generic_subcommand_field() {
+        assert_output(
+            &["-v", "stop"],
+            Cli {
+                verbose: true,
+                cmd: ServerCmd::<u16>::Stop(SubCommandStop {}),
+            },
+        );
+        assert_output(
+            &["connect", "localhost", "-v"],
+            Cli {
+                verbose: true,
+                cmd: ClientCmd::Connect(SubCommandConnect {
+                    addr: "localhost".into(),
+                }),
+            },
+        );
+    }
+
+    #[test]
+    fn generic_subcommand_enum() {
+        assert_output(
+            &["start", "--port", "8080"],
+            Cli {
+                verbose: false,
+                cmd: ServerCmd::Start(SubCommandStart {
+                    port: Some(8080u16),
+                }),
+            },
+        );
+        assert_output(
+            &["start", "--port", "http"],
+            Cli {
+                verbose: false,
+                cmd: ServerCmd::Start(SubCommandStart {
+                    port: Some("http".to_owned()),
+                }),
+            },
+        );
+        assert_error::<Cli<ServerCmd<u16>>>(
+            &["start", "--port", "http"],
+            Error::ParseArgument {
+                arg: "--port".into(),
+                value: "http".into(),
+                msg: "invalid digit found in string".into(),
+            },
+        );
+    }
+
+    #[test]
+    fn help_per_instantiation() {
+        assert_help_string::<Cli<ServerCmd<u16>>>(
+            r###"Usage: test_arg_0 [-v] <command> [<args>]
+
+Shared front-end.
+
+Options:
+  -v, --verbose  Be verbose.
+  -h, --help     Show this help message and exit.
+
+Commands:
+  start          Start the server.
+  stop           Stop the server.
+"###,
+        );
+        assert_help_string::<Cli<ClientCmd>>(
+            r###"Usage: test_arg_0 [-v] <command> [<args>]
+
+Shared front-end.
+
+Options:
+  -v, --verbose  Be verbose.
+  -h, --help     Show this help message and exit.
+
+Commands:
+  connect        Connect to the server.
+"###,
+        );
+    }
+}
+
+mod non_final_repeating_positional {
+    use super::*;
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Copy files.
+    struct Cp {
+        #[argp(switch, short = 'r')]
+        /// Copy directories recursively.
+        recursive: bool,
+
+        #[argp(positional)]
+        /// Files to copy.
+        source: Vec<String>,
+
+        #[argp(positional)]
+        /// Destination directory.
+        dest: String,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Concatenate files.
+    struct Concat {
+        #[argp(positional)]
+        /// Output format.
+        format: String,
+
+        #[argp(positional)]
+        /// Input files.
+        inputs: Vec<String>,
+
+        #[argp(positional)]
+        /// Output file.
+        output: String,
+
+        #[argp(positional)]
+        /// Log file.
+        log: String,
+    }
+
+    #[test]
+    fn trailing_positionals_take_last_values() {
+        assert_output(
+            &["a", "-r", "b", "c", "dir"],
+            Cp {
+                recursive: true,
+                source: vec!["a".into(), "b".into(), "c".into()],
+                dest: "dir".into(),
+            },
+        );
+        assert_output(
+            &["a", "dir"],
+            Cp {
+                recursive: false,
+                source: vec!["a".into()],
+                dest: "dir".into(),
+            },
+        );
+    }
+
+    #[test]
+    fn surrounded_by_positionals() {
+        assert_output(
+            &["md", "a", "b", "out", "log"],
+            Concat {
+                format: "md".into(),
+                inputs: vec!["a".into(), "b".into()],
+                output: "out".into(),
+                log: "log".into(),
+            },
+        );
+    }
+
+    #[test]
+    fn missing_values() {
+        assert_error::<Cp>(
+            &["a"],
+            Error::MissingRequirements(missing_requirements(&["source"], &[], &[])),
+        );
+        assert_error::<Cp>(
+            &[],
+            Error::MissingRequirements(missing_requirements(&["source", "dest"], &[], &[])),
+        );
+        assert_error::<Concat>(
+            &["md", "out", "log"],
+            Error::MissingRequirements(missing_requirements(&["inputs"], &[], &[])),
+        );
+    }
+
+    #[test]
+    fn help() {
+        assert_help_string::<Cp>(
+            r###"Usage: test_arg_0 [-r] <source...> <dest>
+
+Copy files.
+
+Arguments:
+  source           Files to copy.
+  dest             Destination directory.
+
+Options:
+  -r, --recursive  Copy directories recursively.
+  -h, --help       Show this help message and exit.
+"###,
+        );
+    }
+}
+
+mod hyphen_values {
+    use super::*;
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Move a point.
+    struct Move {
+        #[argp(switch, short = 'v')]
+        /// Be verbose.
+        verbose: bool,
+
+        #[argp(option)]
+        /// Offset of the point.
+        offset: Option<i32>,
+
+        #[argp(positional)]
+        /// Delta on the X axis.
+        x: f64,
+
+        #[argp(positional)]
+        /// Delta on the Y axis.
+        y: f64,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Select a column.
+    struct Column {
+        #[argp(switch, short = '1')]
+        /// List one entry per line.
+        one: bool,
+
+        #[argp(positional)]
+        /// Index of the column.
+        index: Option<i32>,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Run a command.
+    struct Run {
+        #[argp(switch, short = 'v')]
+        /// Be verbose.
+        verbose: bool,
+
+        #[argp(positional, allow_hyphen_values)]
+        /// Command arguments.
+        args: Vec<String>,
+    }
+
+    #[test]
+    fn negative_numbers() {
+        assert_output(
+            &["-v", "-5", "-0.5"],
+            Move {
+                verbose: true,
+                offset: None,
+                x: -5.0,
+                y: -0.5,
+            },
+        );
+        assert_output(
+            &["--offset", "-3", "1", "-2"],
+            Move {
+                verbose: false,
+                offset: Some(-3),
+                x: 1.0,
+                y: -2.0,
+            },
+        );
+    }
+
+    #[test]
+    fn negative_numbers_with_numeric_short_option() {
+        assert_output(
+            &["-1"],
+            Column {
+                one: true,
+                index: None,
+            },
+        );
+        assert_output(
+            &["--", "-1"],
+            Column {
+                one: false,
+                index: Some(-1),
+            },
+        );
+        assert_error::<Column>(&["-2"], Error::UnknownArgument("-2".into()));
+    }
+
+    #[test]
+    fn not_negative_numbers() {
+        assert_error::<Move>(&["-1e5", "1"], Error::UnknownArgument("-1".into()));
+        assert_error::<Move>(&["-1.", "1"], Error::UnknownArgument("-1".into()));
+        assert_error::<Move>(&["-.5", "1"], Error::UnknownArgument("-.".into()));
+    }
+
+    #[test]
+    fn negative_number_without_positional() {
+        assert_error::<Move>(&["1", "2", "-3"], Error::UnknownArgument("-3".into()));
+    }
+
+    #[test]
+    fn allow_hyphen_values() {
+        assert_output(
+            &["-x", "--foo=bar", "-v", "-", "--verbose"],
+            Run {
+                verbose: true,
+                args: vec!["-x".into(), "--foo=bar".into(), "-".into()],
+            },
+        );
+        assert_output(
+            &["--", "-v"],
+            Run {
+                verbose: false,
+                args: vec!["-v".into()],
+            },
+        );
+    }
+}
+
+mod options_first {
+    use super::*;
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Run a command with a lower priority.
+    #[argp(options_first)]
+    struct Nice {
+        #[argp(switch, short = 'v')]
+        /// Be verbose.
+        verbose: bool,
+
+        #[argp(option, short = 'n', default = "10")]
+        /// Priority adjustment.
+        adjustment: i32,
+
+        #[argp(positional)]
+        /// Command to run.
+        command: String,
+
+        #[argp(positional)]
+        /// Arguments of the command.
+        args: Vec<String>,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Run a command.
+    #[argp(options_first)]
+    struct Runner {
+        #[argp(switch, short = 'v')]
+        /// Be verbose.
+        verbose: bool,
+
+        #[argp(positional, greedy)]
+        /// Command to run.
+        command: Vec<OsString>,
+    }
+
+    #[test]
+    fn stops_options_at_first_positional() {
+        assert_output(
+            &["-v", "-n", "5", "cmd", "-v", "--help", "--", "x"],
+            Nice {
+                verbose: true,
+                adjustment: 5,
+                command: "cmd".into(),
+                args: vec!["-v".into(), "--help".into(), "--".into(), "x".into()],
+            },
+        );
+        assert_output(
+            &["--", "-cmd", "-n", "5"],
+            Nice {
+                verbose: false,
+                adjustment: 10,
+                command: "-cmd".into(),
+                args: vec!["-n".into(), "5".into()],
+            },
+        );
+    }
+
+    #[test]
+    fn greedy_first_positional() {
+        assert_output(
+            &["-v", "cmd", "-v"],
+            Runner {
+                verbose: true,
+                command: vec!["cmd".into(), "-v".into()],
+            },
+        );
+    }
+}
+
+mod version {
+    use super::*;
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Top level.
+    #[argp(version)]
+    struct CrateVersion {
+        #[argp(option)]
+        /// A required option.
+        name: String,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Top level.
+    #[argp(version = "1.2.3-beta")]
+    struct LiteralVersion {
+        #[argp(switch, short = 'v')]
+        /// Be verbose.
+        verbose: bool,
+
+        #[argp(positional)]
+        /// Some argument.
+        arg: Option<String>,
+    }
+
+    fn version() -> String {
+        format!("{} (build {})", "2.0.0", 42)
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Top level.
+    #[argp(version = version)]
+    struct FunctionVersion {}
+
+    fn assert_version<T: FromArgs + Debug + PartialEq>(args: &[&str], expected: &str) {
+        let res = T::from_args(&["cmdname"], args);
+        assert_eq!(res, Err(EarlyExit::Version(expected.to_owned())));
+    }
+
+    #[test]
+    fn crate_version() {
+        assert_version::<CrateVersion>(&["--version"], env!("CARGO_PKG_VERSION"));
+        assert_version::<CrateVersion>(&["-V"], env!("CARGO_PKG_VERSION"));
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Top level.
+    #[argp(version = "1.0")]
+    struct WithSubCommand {
+        #[argp(subcommand)]
+        cmd: SubCommandWithRequired,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// A subcommand.
+    #[argp(subcommand, name = "sub")]
+    struct SubCommandWithRequired {
+        #[argp(option)]
+        /// A required option.
+        req: String,
+    }
+
+    #[test]
+    fn literal_version() {
+        assert_version::<LiteralVersion>(&["-v", "--version", "foo"], "1.2.3-beta");
+    }
+
+    #[test]
+    fn version_in_short_cluster() {
+        assert_version::<LiteralVersion>(&["-vV"], "1.2.3-beta");
+    }
+
+    #[test]
+    fn version_before_subcommand() {
+        assert_version::<WithSubCommand>(&["--version", "sub"], "1.0");
+        assert_version::<WithSubCommand>(&["--version"], "1.0");
+    }
+
+    #[test]
+    fn version_with_value() {
+        assert_error::<LiteralVersion>(
+            &["--version=1"],
+            Error::UnexpectedArgValue("--version".into()),
+        );
+    }
+
+    #[test]
+    fn function_version() {
+        assert_version::<FunctionVersion>(&["--version"], "2.0.0 (build 42)");
+    }
+
+    #[test]
+    fn help_takes_precedence() {
+        let res = LiteralVersion::from_args(&["cmdname"], &["--version", "--help"]);
+        assert!(matches!(res, Err(EarlyExit::Help(_))));
+    }
+
+    #[test]
+    fn version_after_options_end() {
+        assert_output(
+            &["--", "--version"],
+            LiteralVersion {
+                verbose: false,
+                arg: Some("--version".into()),
+            },
+        );
+    }
+
+    #[test]
+    fn without_version_attribute() {
+        #[derive(FromArgs, Debug)]
+        /// Top level.
+        struct NoVersion {}
+
+        assert_error::<NoVersion>(&["--version"], Error::UnknownArgument("--version".into()));
+        assert_error::<NoVersion>(&["-V"], Error::UnknownArgument("-V".into()));
+    }
+
+    #[test]
+    fn help() {
+        assert_help_string::<LiteralVersion>(
+            r###"Usage: test_arg_0 [-v] [<arg>]
+
+Top level.
+
+Arguments:
+  arg            Some argument.
+
+Options:
+  -v, --verbose  Be verbose.
+  -V, --version  Show version information and exit.
+  -h, --help     Show this help message and exit.
+"###,
+        );
+    }
+}
+
+mod help_triggers {
+    use super::*;
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Show disk usage.
+    #[argp(subcommand, name = "du", help_short = false, help_command = false)]
+    struct Du {
+        #[argp(switch, short = 'h')]
+        /// Print sizes in human readable format.
+        human_readable: bool,
+
+        #[argp(positional)]
+        /// Name of the file.
+        name: Option<String>,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Show a manual page.
+    #[argp(help_short = '?', help_command = "usage")]
+    struct Man {
+        #[argp(positional)]
+        /// Topic of the manual page.
+        topic: String,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Top level.
+    #[argp(help_command = "usage")]
+    struct TopLevel {
+        #[argp(subcommand)]
+        cmd: Cmd,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    #[argp(subcommand)]
+    enum Cmd {
+        Du(Du),
+    }
+
+    #[test]
+    fn disabled_triggers() {
+        assert_output(
+            &["-h", "help"],
+            Du {
+                human_readable: true,
+                name: Some("help".into()),
+            },
+        );
+        let res = Du::from_args(&["cmdname"], &["--help"]);
+        assert!(matches!(res, Err(EarlyExit::Help(_))));
+    }
+
+    #[test]
+    fn renamed_triggers() {
+        assert_output(
+            &["help"],
+            Man {
+                topic: "help".into(),
+            },
+        );
+        assert_error::<Man>(&["-h"], Error::UnknownArgument("-h".into()));
+
+        for args in [&["-?"], &["usage"]] {
+            let res = Man::from_args(&["cmdname"], args);
+            assert!(matches!(res, Err(EarlyExit::Help(_))));
+        }
+    }
+
+    #[test]
+    fn help_of_subcommand_with_disabled_triggers() {
+        let res = TopLevel::from_args(&["cmdname"], &["usage", "du"]);
+        match res {
+            Err(EarlyExit::Help(help)) => {
+                assert!(help.generate_default().starts_with("Usage: cmdname du "));
+            }
+            _ => panic!("expected help"),
+        }
+    }
+
+    #[test]
+    fn missing_subcommand() {
+        let mut missing = MissingRequirements::default();
+        missing.missing_subcommands(["du"].into_iter());
+
+        assert_error::<TopLevel>(&[], Error::MissingRequirements(missing));
+    }
+
+    #[test]
+    fn help() {
+        assert_help_string::<Man>(
+            r###"Usage: test_arg_0 <topic>
+
+Show a manual page.
+
+Arguments:
+  topic       Topic of the manual page.
+
+Options:
+  -?, --help  Show this help message and exit.
+"###,
+        );
+    }
+
+    #[test]
+    fn help_without_short() {
+        let res = Du::from_args(&["test_arg_0"], &["--help"]);
+        match res {
+            Err(EarlyExit::Help(help)) => assert_eq!(
+                help.generate(&FIXED_HELP_STYLE),
+                r###"Usage: test_arg_0 [-h] [<name>]
+
+Show disk usage.
+
+Arguments:
+  name                  Name of the file.
+
+Options:
+  -h, --human-readable  Print sizes in human readable format.
+      --help            Show this help message and exit.
+"###
+            ),
+            _ => panic!("expected help"),
+        }
+    }
+}
+
+mod abbreviations {
+    use super::*;
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Top level.
+    #[argp(abbreviations)]
+    struct TopLevel {
+        #[argp(switch, global)]
+        /// Be verbose.
+        verbose: bool,
+
+        #[argp(option)]
+        /// A version.
+        version: Option<String>,
+
+        #[argp(subcommand)]
+        nested: Option<SubCommandEnum>,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    #[argp(subcommand)]
+    enum SubCommandEnum {
+        One(SubCommandOne),
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// First subcommand.
+    #[argp(subcommand, name = "one")]
+    struct SubCommandOne {
+        #[argp(option)]
+        /// A value.
+        value: Option<String>,
+
+        #[argp(option)]
+        /// A variant.
+        variant: Option<String>,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Without abbreviations.
+    struct Disabled {
+        #[argp(switch)]
+        /// Be verbose.
+        verbose: bool,
+    }
+
+    #[test]
+    fn unique_prefix() {
+        assert_output(
+            &["--verb", "--vers=1"],
+            TopLevel {
+                verbose: true,
+                version: Some("1".into()),
+                nested: None,
+            },
+        );
+        assert_output(
+            &["one", "--verb", "--val", "x", "--vari=y"],
+            TopLevel {
+                verbose: true,
+                version: None,
+                nested: Some(SubCommandEnum::One(SubCommandOne {
+                    value: Some("x".into()),
+                    variant: Some("y".into()),
+                })),
+            },
+        );
+    }
+
+    #[test]
+    fn exact_match_wins() {
+        #[derive(FromArgs, PartialEq, Debug)]
+        /// Short description
+        #[argp(abbreviations)]
+        struct Cmd {
+            #[argp(switch)]
+            /// foo
+            foo: bool,
+            #[argp(switch)]
+            /// foobar
+            foobar: bool,
+        }
+
+        assert_output(
+            &["--foo"],
+            Cmd {
+                foo: true,
+                foobar: false,
+            },
+        );
+        assert_output(
+            &["--foob"],
+            Cmd {
+                foo: false,
+                foobar: true,
+            },
+        );
+    }
+
+    #[test]
+    fn ambiguous() {
+        assert_error::<TopLevel>(
+            &["--ver"],
+            Error::AmbiguousOption {
+                arg: "--ver".into(),
+                candidates: vec!["--verbose", "--version"],
+            },
+        );
+        assert_error::<TopLevel>(
+            &["one", "--v"],
+            Error::AmbiguousOption {
+                arg: "--v".into(),
+                candidates: vec!["--value", "--variant", "--verbose"],
+            },
+        );
+        assert_eq!(
+            Error::AmbiguousOption {
+                arg: "--ver".into(),
+                candidates: vec!["--verbose", "--version"],
+            }
+            .to_string(),
+            "Option '--ver' is ambiguous; possibilities: '--verbose' '--version'."
+        );
+    }
+
+    #[test]
+    fn empty_name_is_not_prefix() {
+        assert_error::<TopLevel>(&["--=x"], Error::UnknownArgument("--".into()));
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        assert_error::<Disabled>(&["--verb"], Error::UnknownArgument("--verb".into()));
+    }
+}
+
+#[cfg(feature = "suggestions")]
+mod suggestions {
+    use super::*;
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Top level.
+    #[argp(suggestions)]
+    struct TopLevel {
+        #[argp(switch, global)]
+        /// Be verbose.
+        verbose: bool,
+
+        #[argp(option)]
+        /// How high to go.
+        height: Option<usize>,
+
+        #[argp(subcommand)]
+        nested: Option<SubCommandEnum>,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    #[argp(subcommand)]
+    enum SubCommandEnum {
+        Status(StatusCmd),
+        Remove(RemoveCmd),
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Show status.
+    #[argp(subcommand, name = "status")]
+    struct StatusCmd {
+        #[argp(switch)]
+        /// Show all.
+        all: bool,
+    }
+
+    #[derive(FromArgs, PartialEq, Debug)]
+    /// Remove a file.
+    #[argp(subcommand, name = "remove", alias = "delete", hidden_alias = "erase")]
+    struct RemoveCmd {}
+
+    fn suggestion(arg: &str, suggestion: &'static str) -> Error {
+        Error::UnknownArgumentSuggestion {
+            arg: arg.into(),
+            suggestion,
+        }
+    }
+
+    #[test]
+    fn unknown_option() {
+        assert_error::<TopLevel>(&["--hieght", "5"], suggestion("--hieght", "--height"));
+        assert_error::<TopLevel>(&["--hepl"], suggestion("--hepl", "--help"));
+        assert_error::<TopLevel>(&["status", "--verbos"], suggestion("--verbos", "--verbose"));
+        assert_error::<TopLevel>(&["--nope"], Error::UnknownArgument("--nope".into()));
+        assert_error::<TopLevel>(&["-x"], Error::UnknownArgument("-x".into()));
+    }
+
+    #[test]
+    fn unknown_subcommand() {
+        assert_error::<TopLevel>(&["sttaus"], suggestion("sttaus", "status"));
+        assert_error::<TopLevel>(&["delte"], suggestion("delte", "delete"));
+        assert_error::<TopLevel>(&["erse"], Error::UnknownArgument("erse".into()));
+        assert_error::<TopLevel>(&["foo"], Error::UnknownArgument("foo".into()));
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        #[derive(FromArgs, PartialEq, Debug)]
+        /// Top level.
+        struct TopLevel {
+            #[argp(option)]
+            /// How high to go.
+            height: Option<usize>,
+        }
+
+        assert_error::<TopLevel>(&["--hieght", "5"], Error::UnknownArgument("--hieght".into()));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            suggestion("--hieght", "--height").to_string(),
+            "Unrecognized argument: --hieght\nDid you mean '--height'?"
+        );
+    }
+}
+
+mod env {
+    use super::*;
+
+    // NOTE: Each test uses its own environment variables because tests are run
+    // in parallel.
+
+    #[test]
+    fn fallback() {
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Woot
+        struct Cmd {
+            /// port
+            #[argp(option, env = "ARGP_TEST_FALLBACK_PORT")]
+            port: u16,
+
+            /// host
+            #[argp(
+                option,
+                env = "ARGP_TEST_FALLBACK_HOST",
+                default = "String::from(\"localhost\")"
+            )]
+            host: String,
+        }
+
+        std::env::set_var("ARGP_TEST_FALLBACK_PORT", "8080");
+
+        assert_output(
+            &[],
+            Cmd {
+                port: 8080,
+                host: "localhost".to_owned(),
+            },
+        );
+        assert_output(
+            &["--port", "9090"],
+            Cmd {
+                port: 9090,
+                host: "localhost".to_owned(),
+            },
+        );
+
+        std::env::set_var("ARGP_TEST_FALLBACK_HOST", "example.org");
+
+        assert_output(
+            &[],
+            Cmd {
+                port: 8080,
+                host: "example.org".to_owned(),
+            },
+        );
+    }
+
+    #[test]
+    fn missing() {
+        #[derive(FromArgs, Debug)]
+        /// Woot
+        struct Cmd {
+            /// port
+            #[argp(option, env = "ARGP_TEST_MISSING_PORT")]
+            _port: u16,
+        }
+
+        assert_error::<Cmd>(
+            &[],
+            Error::MissingRequirements(missing_requirements(&[], &["--port"], &[])),
+        );
+    }
+
+    #[test]
+    fn invalid_value() {
+        #[derive(FromArgs, Debug)]
+        /// Woot
+        struct Cmd {
+            /// port
+            #[argp(option, env = "ARGP_TEST_INVALID_PORT")]
+            _port: Option<u16>,
+
+            /// verbose
+            #[argp(switch, env = "ARGP_TEST_INVALID_VERBOSE")]
+            _verbose: bool,
+        }
+
+        std::env::set_var("ARGP_TEST_INVALID_PORT", "http");
+
+        assert_error::<Cmd>(
+            &[],
+            Error::ParseEnvVar {
+                var: "ARGP_TEST_INVALID_PORT".to_owned(),
+                value: "http".into(),
+                msg: "invalid digit found in string".to_owned(),
+            },
+        );
+
+        std::env::set_var("ARGP_TEST_INVALID_PORT", "80");
+        std::env::set_var("ARGP_TEST_INVALID_VERBOSE", "maybe");
+
+        assert_error::<Cmd>(
+            &[],
+            Error::ParseEnvVar {
+                var: "ARGP_TEST_INVALID_VERBOSE".to_owned(),
+                value: "maybe".into(),
+                msg: "expected a boolean value".to_owned(),
+            },
+        );
+    }
+
+    #[test]
+    fn env_prefix() {
+        #[derive(FromArgs, Debug, PartialEq)]
+        #[argp(env_prefix = "ARGP_TEST_PREFIX_")]
+        /// Woot
+        struct Cmd {
+            /// dry run
+            #[argp(switch)]
+            dry_run: bool,
+
+            /// color
+            #[argp(switch, negatable)]
+            color: Option<bool>,
+
+            /// log level
+            #[argp(option, env = "ARGP_TEST_LOG_LEVEL")]
+            log_level: Option<String>,
+        }
+
+        std::env::set_var("ARGP_TEST_PREFIX_DRY_RUN", "yes");
+        std::env::set_var("ARGP_TEST_PREFIX_COLOR", "0");
+        std::env::set_var("ARGP_TEST_LOG_LEVEL", "debug");
+
+        assert_output(
+            &[],
+            Cmd {
+                dry_run: true,
+                color: Some(false),
+                log_level: Some("debug".to_owned()),
+            },
+        );
+        assert_output(
+            &["--color"],
+            Cmd {
+                dry_run: true,
+                color: Some(true),
+                log_level: Some("debug".to_owned()),
+            },
+        );
+    }
+
+    #[test]
+    fn help() {
+        #[derive(FromArgs, Debug)]
+        /// Woot
+        struct Cmd {
+            /// port to listen on
+            #[argp(option, env = "APP_PORT")]
+            _port: Option<u16>,
+        }
 
-Short description
+        assert_help_string::<Cmd>(
+            r###"Usage: test_arg_0 [--port <port>]
+
+Woot
 
 Options:
-      --s     a switch with a description that is spread across a number of
-              lines of comments.
-  -h, --help  Show this help message and exit.
+      --port <port>  port to listen on [env: APP_PORT]
+  -h, --help         Show this help message and exit.
 "###,
-    );
+        );
+    }
 }
 
-#[test]
-fn default_number() {
-    #[derive(FromArgs)]
-    /// Short description
+mod config {
+    use super::*;
+    use argp::config::KeyValueConfig;
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
     struct Cmd {
-        #[argp(option, default = "5")]
-        /// fooey
-        x: u8,
-    }
+        /// port
+        #[argp(option)]
+        port: u16,
 
-    let cmd = Cmd::from_args(&["cmdname"], EMPTY_ARGS).unwrap();
-    assert_eq!(cmd.x, 5);
-}
+        /// host
+        #[argp(option, default = "String::from(\"localhost\")")]
+        host: String,
 
-#[test]
-fn default_function() {
-    const MSG: &str = "hey I just met you";
-    fn call_me_maybe() -> String {
-        MSG.to_owned()
+        /// verbose
+        #[argp(switch, short = 'v')]
+        verbose: bool,
+
+        /// include
+        #[argp(option)]
+        include: Vec<String>,
     }
 
-    #[derive(FromArgs)]
-    /// Short description
-    struct Cmd {
-        #[argp(option, default = "call_me_maybe()")]
-        /// fooey
-        msg: String,
+    fn assert_config_output<T: FromArgs + Debug + PartialEq>(
+        config: &str,
+        args: &[&str],
+        expected: T,
+    ) {
+        let config = KeyValueConfig::parse("test.conf", config).unwrap();
+        let t = T::from_args_with_config(&["cmd"], args, &config).expect("failed to parse");
+        assert_eq!(t, expected);
     }
 
-    let cmd = Cmd::from_args(&["cmdname"], EMPTY_ARGS).unwrap();
-    assert_eq!(cmd.msg, MSG);
-}
+    fn assert_config_error<T: FromArgs + Debug>(config: &str, args: &[&str], expected: Error) {
+        let config = KeyValueConfig::parse("test.conf", config).unwrap();
+        let e = T::from_args_with_config(&["cmd"], args, &config)
+            .expect_err("unexpectedly succeeded parsing");
+        assert_eq!(EarlyExit::Err(expected), e);
+    }
 
-#[test]
-fn missing_option_value() {
-    #[derive(FromArgs, Debug)]
-    /// Short description
-    struct Cmd {
-        #[argp(option)]
-        /// fooey
-        _msg: String,
+    #[test]
+    fn precedence() {
+        assert_config_output(
+            "port = 80\nverbose = yes\ninclude = a\ninclude = b",
+            &[],
+            Cmd {
+                port: 80,
+                host: "localhost".to_owned(),
+                verbose: true,
+                include: vec!["a".to_owned(), "b".to_owned()],
+            },
+        );
+        assert_config_output(
+            "port = 80\nhost = example.org\ninclude = a",
+            &["--port", "8080", "--include", "c"],
+            Cmd {
+                port: 8080,
+                host: "example.org".to_owned(),
+                verbose: false,
+                include: vec!["c".to_owned()],
+            },
+        );
     }
 
-    let e = Cmd::from_args(&["cmdname"], &["--msg"])
-        .expect_err("Parsing missing option value should fail");
-    assert_eq!(e, EarlyExit::Err(Error::MissingArgValue("--msg".to_owned())));
-}
+    #[test]
+    fn missing() {
+        assert_config_error::<Cmd>(
+            "host = example.org",
+            &[],
+            Error::MissingRequirements(missing_requirements(&[], &["--port"], &[])),
+        );
+    }
 
-fn assert_help_string<T: FromArgs>(help_str: &str) {
-    match T::from_args(&["test_arg_0"], &["--help"]) {
-        Ok(_) => panic!("help was parsed as args"),
-        Err(EarlyExit::Err(_)) => panic!("expected EarlyExit::Help"),
-        Err(EarlyExit::Help(help)) => {
-            assert_eq!(help.generate(&FIXED_HELP_STYLE), help_str);
-        }
+    #[test]
+    fn invalid_value() {
+        assert_config_error::<Cmd>(
+            "port = http",
+            &[],
+            Error::ParseConfigValue {
+                source: "test.conf".to_owned(),
+                key: "port".to_owned(),
+                value: "http".into(),
+                msg: "invalid digit found in string".to_owned(),
+            },
+        );
+        assert_config_error::<Cmd>(
+            "port = 80\nport = 81",
+            &[],
+            Error::ParseConfigValue {
+                source: "test.conf".to_owned(),
+                key: "port".to_owned(),
+                value: "81".into(),
+                msg: "can only be specified once".to_owned(),
+            },
+        );
+        assert_config_error::<Cmd>(
+            "port = 80\nverbose = maybe",
+            &[],
+            Error::ParseConfigValue {
+                source: "test.conf".to_owned(),
+                key: "verbose".to_owned(),
+                value: "maybe".into(),
+                msg: "expected a boolean value".to_owned(),
+            },
+        );
     }
-}
 
-fn assert_output<T: FromArgs + Debug + PartialEq>(args: &[&str], expected: T) {
-    let t = T::from_args(&["cmd"], args).expect("failed to parse");
-    assert_eq!(t, expected);
-}
+    #[test]
+    fn subcommand() {
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Top-level command.
+        struct TopLevel {
+            /// verbose
+            #[argp(switch, global)]
+            verbose: bool,
 
-fn assert_error<T: FromArgs + Debug>(args: &[&str], expected: Error) {
-    let e = T::from_args(&["cmd"], args).expect_err("unexpectedly succeeded parsing");
-    assert_eq!(EarlyExit::Err(expected), e);
-}
+            #[argp(subcommand)]
+            sub: Sub,
+        }
 
-fn missing_requirements(
-    positionals: &[&'static str],
-    options: &[&'static str],
-    subcommands: &[&'static str],
-) -> MissingRequirements {
-    let mut missing = MissingRequirements::default();
+        #[derive(FromArgs, Debug, PartialEq)]
+        #[argp(subcommand, name = "sub")]
+        /// Subcommand.
+        struct Sub {
+            /// name
+            #[argp(option)]
+            name: String,
+        }
 
-    for pos in positionals {
-        missing.missing_positional_arg(pos);
-    }
-    for opt in options {
-        missing.missing_option(opt);
-    }
-    if !subcommands.is_empty() {
-        missing.missing_subcommands(subcommands.iter().copied());
+        assert_config_output(
+            "verbose = true\nname = foo",
+            &["sub"],
+            TopLevel {
+                verbose: true,
+                sub: Sub {
+                    name: "foo".to_owned(),
+                },
+            },
+        );
     }
-
-    missing
 }
 
-mod options {
+mod response_files {
     use super::*;
+    use std::fs;
+    use std::path::{Path, PathBuf};
 
     #[derive(FromArgs, Debug, PartialEq)]
+    #[argp(response_files)]
     /// Woot
-    struct Parsed {
-        #[argp(option, short = 'n')]
-        /// fooey
-        n: usize,
+    struct Cmd {
+        /// define
+        #[argp(option, short = 'D')]
+        define: Vec<String>,
+
+        /// files
+        #[argp(positional)]
+        files: Vec<String>,
     }
 
-    #[test]
-    fn parsed() {
-        assert_output(&["-n", "5"], Parsed { n: 5 });
-        assert_error::<Parsed>(
-            &["-n", "x"],
-            Error::ParseArgument {
-                arg: "-n".to_owned(),
-                value: "x".into(),
-                msg: "invalid digit found in string".to_owned(),
-            },
-        );
+    /// Writes a response file with the given content into a temporary
+    /// directory and returns its path.
+    fn write_rsp(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("argp-test-{}-{}", std::process::id(), name));
+        fs::write(&path, content).unwrap();
+        path
     }
 
-    #[derive(FromArgs, Debug, PartialEq)]
-    /// Woot
-    struct Repeating {
-        #[argp(option, short = 'n')]
-        /// fooey
-        n: Vec<String>,
+    fn at(path: &Path) -> String {
+        format!("@{}", path.display())
     }
 
     #[test]
-    fn repeating() {
-        assert_help_string::<Repeating>(
-            r###"Usage: test_arg_0 [-n <n...>]
-
-Woot
+    fn expand() {
+        let path = write_rsp("expand.rsp", "-D a\n'b c' -Dx=\"1 2\"\n");
 
-Options:
-  -n, --n <n>  fooey
-  -h, --help   Show this help message and exit.
-"###,
+        assert_output(
+            &["first", &at(&path), "last"],
+            Cmd {
+                define: vec!["a".to_owned(), "x=1 2".to_owned()],
+                files: vec!["first".to_owned(), "b c".to_owned(), "last".to_owned()],
+            },
         );
     }
 
-    #[derive(FromArgs, Debug, PartialEq)]
-    /// Woot
-    struct WithArgName {
-        #[argp(option, arg_name = "name")]
-        /// fooey
-        option_name: Option<String>,
-    }
-
     #[test]
-    fn with_arg_name() {
-        assert_help_string::<WithArgName>(
-            r###"Usage: test_arg_0 [--option-name <name>]
+    fn nested() {
+        let inner = write_rsp("nested-inner.rsp", "inner");
+        let outer = write_rsp("nested-outer.rsp", &format!("outer {}", at(&inner)));
 
-Woot
+        assert_output(
+            &[&at(&outer), &at(&inner)],
+            Cmd {
+                define: vec![],
+                files: vec!["outer".to_owned(), "inner".to_owned(), "inner".to_owned()],
+            },
+        );
+    }
 
-Options:
-      --option-name <name>  fooey
-  -h, --help                Show this help message and exit.
-"###,
+    #[test]
+    fn cycle() {
+        let a = std::env::temp_dir().join(format!("argp-test-{}-cycle-a.rsp", std::process::id()));
+        let b = write_rsp("cycle-b.rsp", &at(&a));
+        fs::write(&a, at(&b)).unwrap();
+
+        assert_error::<Cmd>(
+            &[&at(&a)],
+            Error::ResponseFile {
+                path: a.clone(),
+                msg: "the file includes itself recursively".to_owned(),
+            },
         );
     }
 
-    /// Woot
-    #[derive(FromArgs, Debug, PartialEq)]
-    struct ShortCombined {
-        /// fooey
-        #[argp(option, short = 'n')]
-        n: usize,
-        /// quiet
-        #[argp(switch, short = 'q')]
-        q: bool,
-        /// verbose
-        #[argp(switch, short = 'v')]
-        v: bool,
+    #[test]
+    fn missing_file() {
+        let path = std::env::temp_dir().join("argp-test-does-not-exist.rsp");
+
+        match Cmd::from_args(&["cmd"], &[at(&path)]) {
+            Err(EarlyExit::Err(Error::ResponseFile { path: p, .. })) => assert_eq!(p, path),
+            res => panic!("expected Error::ResponseFile, got {:?}", res),
+        }
     }
 
     #[test]
-    fn short_combined() {
-        assert_output(
-            &["-qv", "-n", "5"],
-            ShortCombined {
-                n: 5,
-                q: true,
-                v: true,
-            },
-        );
+    fn disabled_by_default() {
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Woot
+        struct Cmd {
+            /// files
+            #[argp(positional)]
+            files: Vec<String>,
+        }
+
         assert_output(
-            &["-qvn", "5"],
-            ShortCombined {
-                n: 5,
-                q: true,
-                v: true,
+            &["@foo", "@"],
+            Cmd {
+                files: vec!["@foo".to_owned(), "@".to_owned()],
             },
         );
-        assert_error::<ShortCombined>(&["-nq", "5"], Error::MissingArgValue("-n".to_owned()));
     }
 }
 
@@ -632,6 +3641,31 @@ mod global_options {
         }
     }
 
+    #[test]
+    fn parse_attached_value() {
+        for args in [
+            &["--a=1", "--x=2", "one", "--b"],
+            &["--x=2", "one", "--a=1", "--b"],
+        ] {
+            let actual = TopLevel::from_args(&["cmdname"], args).expect("sc 1");
+            assert_eq!(
+                actual,
+                TopLevel {
+                    a: 1,
+                    x: 2,
+                    nested: FirstSubCommandEnum::One(SubCommandOne {
+                        b: true,
+                        nested: None
+                    })
+                },
+            );
+        }
+
+        let e = TopLevel::from_args(&["cmdname"], &["one", "two", "--a=1", "--b=yes"])
+            .expect_err("unexpectedly succeeded parsing");
+        assert_eq!(e.to_string(), "Option '--b' doesn't allow a value.");
+    }
+
     #[test]
     fn help() {
         expect_help(
@@ -1310,10 +4344,9 @@ Options:
 
     impl DynamicSubCommand for HelpExamplePlugin {
         fn commands() -> &'static [&'static CommandInfo] {
-            &[&CommandInfo {
-                name: "plugin",
-                description: "Example dynamic command",
-            }]
+            static COMMANDS: &[&CommandInfo] =
+                &[&CommandInfo::new("plugin", "Example dynamic command")];
+            COMMANDS
         }
 
         fn try_from_args(
@@ -1956,6 +4989,18 @@ Options:
             Cmd::from_args(&["cmdname"], &[OsStr::new("-m"), msg, path]).expect("failed to parse");
         assert_eq!(s.msg, msg.to_os_string());
         assert_eq!(s.path, PathBuf::from(path));
+
+        let mut attached = OsString::from("--msg=");
+        attached.push(msg);
+
+        let s = Cmd::from_args(&["cmdname"], &[&attached, path]).expect("failed to parse");
+        assert_eq!(s.msg, msg.to_os_string());
+
+        let mut attached = OsString::from("-m");
+        attached.push(msg);
+
+        let s = Cmd::from_args(&["cmdname"], &[&attached, path]).expect("failed to parse");
+        assert_eq!(s.msg, msg.to_os_string());
     }
 
     #[test]
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// SPDX-FileCopyrightText: 2023 Jakub Jirutka <jakub@jirutka.cz>
+
+// This is a separate test binary, because the `POSIXLY_CORRECT` environment
+// variable affects all commands parsed in the process.
+
+use std::ffi::OsString;
+
+use argp::FromArgs;
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Run a command with a timeout.
+struct Timeout {
+    #[argp(switch, short = 'v')]
+    /// Be verbose.
+    verbose: bool,
+
+    #[argp(positional)]
+    /// Duration in seconds.
+    duration: u32,
+
+    #[argp(positional)]
+    /// Command to run.
+    command: Vec<OsString>,
+}
+
+#[test]
+fn posixly_correct_stops_options_at_first_positional() {
+    std::env::set_var("POSIXLY_CORRECT", "1");
+
+    assert_eq!(
+        Timeout::from_args(&["cmdname"], &["-v", "5", "ls", "-v", "--", "-l"]),
+        Ok(Timeout {
+            verbose: true,
+            duration: 5,
+            command: vec!["ls".into(), "-v".into(), "--".into(), "-l".into()],
+        })
+    );
+}
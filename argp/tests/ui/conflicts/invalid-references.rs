@@ -0,0 +1,19 @@
+/// Command
+#[derive(argp::FromArgs)]
+#[argp(group(name = "format", required))]
+#[argp(group(name = "empty"))]
+struct Cmd {
+    /// json
+    #[argp(switch, group = "format", conflicts_with = "nope")]
+    json: bool,
+
+    /// yaml
+    #[argp(option, group = "fromat")]
+    yaml: Option<String>,
+
+    /// table
+    #[argp(option, group = "format")]
+    table: String,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+/// Command
+#[derive(argp::FromArgs)]
+struct Cmd {
+    /// color
+    #[argp(option, alias = "--colour", hidden_alias = "-c")]
+    color: String,
+
+    /// colour
+    #[argp(option, short = 'c')]
+    colour: String,
+
+    /// invalid
+    #[argp(option, aliases = ["invalid", "-ab"])]
+    invalid: String,
+}
+
+fn main() {}
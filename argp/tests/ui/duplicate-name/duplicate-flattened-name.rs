@@ -0,0 +1,20 @@
+/// Logging
+#[derive(argp::FromArgs)]
+struct Logging {
+    /// verbose
+    #[argp(switch, short = 'v')]
+    verbose: bool,
+}
+
+/// Command
+#[derive(argp::FromArgs)]
+struct Cmd {
+    /// version
+    #[argp(switch, short = 'v')]
+    version: bool,
+
+    #[argp(flatten)]
+    logging: Logging,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+/// Command
+#[derive(argp::FromArgs)]
+struct Cmd {
+    /// color
+    #[argp(switch, negatable)]
+    color: bool,
+
+    /// no color
+    #[argp(switch)]
+    no_color: bool,
+}
+
+fn main() {}
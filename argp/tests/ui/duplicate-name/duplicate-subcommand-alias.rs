@@ -0,0 +1,27 @@
+/// Command
+#[derive(argp::FromArgs)]
+struct Cmd {
+    #[argp(subcommand)]
+    nested: SubCommands,
+}
+
+#[derive(argp::FromArgs)]
+#[argp(subcommand)]
+enum SubCommands {
+    Remove(Remove),
+    Rm(Rm),
+}
+
+/// Remove
+#[derive(argp::FromArgs)]
+#[argp(subcommand, name = "remove", alias = "rm")]
+struct Remove {}
+
+/// Rm
+#[derive(argp::FromArgs)]
+#[argp(subcommand, name = "rm")]
+struct Rm {}
+
+fn main() {
+    let _ = argp::parse_args_or_exit::<Cmd>;
+}
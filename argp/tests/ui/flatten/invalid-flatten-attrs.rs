@@ -0,0 +1,33 @@
+/// Command
+#[derive(argp::FromArgs)]
+struct GlobalWithoutSubCommand {
+    /// verbose
+    #[argp(switch, global)]
+    verbose: bool,
+}
+
+/// Options
+#[derive(argp::FromArgs)]
+#[argp(flatten)]
+struct FlattenWithSubCommand {
+    #[argp(subcommand)]
+    cmd: SubCommands,
+}
+
+#[derive(argp::FromArgs)]
+#[argp(subcommand)]
+enum SubCommands {
+    /// Run.
+    Run,
+}
+
+/// Options
+#[derive(argp::FromArgs)]
+#[argp(flatten, subcommand, name = "opts")]
+struct FlattenSubCommand {
+    /// verbose
+    #[argp(switch)]
+    verbose: bool,
+}
+
+fn main() {}
@@ -0,0 +1,38 @@
+/// Logging
+#[derive(argp::FromArgs)]
+#[argp(flatten)]
+struct Logging {
+    /// verbose
+    #[argp(switch, global)]
+    verbose: bool,
+}
+
+/// Command
+#[derive(argp::FromArgs)]
+struct GlobalWithoutSubCommand {
+    #[argp(flatten)]
+    logging: Logging,
+}
+
+/// Nested
+#[derive(argp::FromArgs)]
+struct Nested {
+    #[argp(subcommand)]
+    cmd: SubCommands,
+}
+
+#[derive(argp::FromArgs)]
+#[argp(subcommand)]
+enum SubCommands {
+    /// Run.
+    Run,
+}
+
+/// Command
+#[derive(argp::FromArgs)]
+struct FlattenedSubCommand {
+    #[argp(flatten)]
+    nested: Nested,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+#[derive(argp::FromArgValue)]
+enum Format {
+    Json,
+    #[argp(alias = "json")]
+    Yaml,
+    Table(String),
+}
+
+#[derive(argp::FromArgValue)]
+#[argp(case_insensitive)]
+struct Pair(u8, u8);
+
+fn main() {}
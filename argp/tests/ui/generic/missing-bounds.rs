@@ -0,0 +1,34 @@
+use argp::FromArgs;
+
+/// Command
+#[derive(FromArgs)]
+struct Cli<S> {
+    #[argp(subcommand)]
+    cmd: S,
+}
+
+/// Command
+#[derive(FromArgs)]
+struct WithValue<T> {
+    /// value
+    #[argp(option)]
+    value: Option<T>,
+}
+
+#[derive(FromArgs)]
+#[argp(subcommand)]
+enum Cmd<T> {
+    One(T),
+}
+
+/// Not a subcommand
+#[derive(FromArgs)]
+struct NotSubCommand {}
+
+struct NotValue;
+
+fn main() {
+    let _ = Cli::<NotSubCommand>::from_args(&["cmd"], &["one"]);
+    let _ = WithValue::<NotValue>::from_args(&["cmd"], &[]);
+    let _ = Cli::<Cmd<NotSubCommand>>::from_args(&["cmd"], &["one"]);
+}
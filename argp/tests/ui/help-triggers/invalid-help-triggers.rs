@@ -0,0 +1,35 @@
+#[derive(argp::FromArgs)]
+/// Short name used by the help option.
+struct ShortHelpName {
+    #[argp(switch, short = 'h')]
+    /// Print sizes in human readable format.
+    human_readable: bool,
+}
+
+#[derive(argp::FromArgs)]
+/// Short name used by the renamed help option.
+#[argp(help_short = 'H')]
+struct RenamedShortHelpName {
+    #[argp(switch, short = 'H')]
+    /// Print sizes in human readable format.
+    human_readable: bool,
+}
+
+#[derive(argp::FromArgs)]
+/// Aliases used by the help option.
+struct HelpAliases {
+    #[argp(switch, alias = "-h")]
+    /// Print sizes in human readable format.
+    human_readable: bool,
+
+    #[argp(switch, hidden_alias = "--help")]
+    /// Show the value.
+    value: bool,
+}
+
+#[derive(argp::FromArgs)]
+/// Invalid values.
+#[argp(help_command = "-help", help_short = true)]
+struct InvalidValues {}
+
+fn main() {}
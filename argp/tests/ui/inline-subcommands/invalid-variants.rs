@@ -0,0 +1,36 @@
+#[derive(argp::FromArgs)]
+#[argp(subcommand)]
+enum Cmd {
+    /// Two fields
+    Two(String, String),
+
+    Undocumented {
+        /// name
+        #[argp(positional)]
+        name: String,
+    },
+
+    /// Dynamic
+    #[argp(dynamic)]
+    Dynamic,
+}
+
+/// Generic
+#[derive(argp::FromArgs)]
+#[argp(subcommand)]
+enum Generic<T: argp::FromArgs + argp::SubCommand> {
+    Wrapped(T),
+    /// Inline
+    Inline,
+}
+
+/// Valid, attributes of other derives are not copied to the inline struct.
+#[derive(argp::FromArgs, Default)]
+#[argp(subcommand)]
+enum WithDefault {
+    /// Inline
+    #[default]
+    Inline,
+}
+
+fn main() {}
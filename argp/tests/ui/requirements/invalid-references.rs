@@ -0,0 +1,21 @@
+/// Command
+#[derive(argp::FromArgs)]
+struct Cmd {
+    /// key
+    #[argp(option, requires = "cert")]
+    key: Option<String>,
+
+    /// output
+    #[argp(option, required_unless = "output")]
+    output: Option<String>,
+
+    /// token
+    #[argp(option, required_if = "verbose=yes")]
+    token: String,
+
+    /// verbose
+    #[argp(switch)]
+    verbose: bool,
+}
+
+fn main() {}
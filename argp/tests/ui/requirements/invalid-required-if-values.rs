@@ -0,0 +1,31 @@
+#[derive(argp::FromArgValue, PartialEq)]
+enum Mode {
+    Fast,
+    Slow,
+}
+
+/// Command
+#[derive(argp::FromArgs)]
+struct Cmd {
+    /// mode
+    #[argp(option)]
+    mode: Option<Mode>,
+
+    /// auth
+    #[argp(option, possible_values = ["none", "token"])]
+    auth: Option<String>,
+
+    /// threads
+    #[argp(option, required_if = "mode=medium")]
+    threads: Option<usize>,
+
+    /// token
+    #[argp(option, required_if = "auth=tokne")]
+    token: Option<String>,
+
+    /// level
+    #[argp(option, required_if = "mode=fast")]
+    level: Option<usize>,
+}
+
+fn main() {}
@@ -0,0 +1,31 @@
+#[derive(argp::FromArgs)]
+/// Top-level command.
+struct TopLevel {
+    #[argp(subcommand)]
+    nested: MySubCommandEnum,
+}
+
+#[derive(argp::FromArgs)]
+#[argp(subcommand)]
+enum MySubCommandEnum {
+    One(SubCommandOne),
+    Two(SubCommandTwo),
+    Three(SubCommandThree),
+}
+
+#[derive(argp::FromArgs)]
+/// Response files on a subcommand.
+#[argp(subcommand, name = "one", response_files)]
+struct SubCommandOne {}
+
+#[derive(argp::FromArgs)]
+/// Abbreviations on a subcommand.
+#[argp(subcommand, name = "two", abbreviations)]
+struct SubCommandTwo {}
+
+#[derive(argp::FromArgs)]
+/// Suggestions on a subcommand.
+#[argp(subcommand, name = "three", suggestions)]
+struct SubCommandThree {}
+
+fn main() {}
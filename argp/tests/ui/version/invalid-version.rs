@@ -0,0 +1,38 @@
+#[derive(argp::FromArgs)]
+/// Custom `--version` flag.
+#[argp(version)]
+struct CustomVersionFlag {
+    #[argp(switch)]
+    /// Show version.
+    version: bool,
+}
+
+#[derive(argp::FromArgs)]
+/// Short name used by the version option.
+#[argp(version)]
+struct CustomVersionNames {
+    #[argp(switch, short = 'V')]
+    /// Be verbose.
+    verbose: bool,
+}
+
+#[derive(argp::FromArgs)]
+/// Aliases used by the version option.
+#[argp(version)]
+struct CustomVersionAliases {
+    #[argp(switch, alias = "-V", hidden_alias = "--version")]
+    /// Show the value.
+    value: bool,
+}
+
+#[derive(argp::FromArgs)]
+/// Invalid value.
+#[argp(version = 1)]
+struct InvalidValue {}
+
+#[derive(argp::FromArgs)]
+/// Duplicate attribute.
+#[argp(version, version = "1.0")]
+struct DuplicateVersion {}
+
+fn main() {}
@@ -67,6 +67,7 @@ impl Errors {
     expect_lit_fn![
         (expect_lit_str, LitStr, Str, "string"),
         (expect_lit_char, LitChar, Char, "character"),
+        (expect_lit_bool, LitBool, Bool, "boolean"),
     ];
 
     expect_meta_fn![
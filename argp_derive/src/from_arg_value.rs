@@ -0,0 +1,204 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// SPDX-FileCopyrightText: 2023 Jakub Jirutka <jakub@jirutka.cz>
+
+//! Implementation of the `FromArgValue` derive.
+
+use std::collections::HashMap;
+
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::LitStr;
+
+use crate::errors::Errors;
+use crate::parse_attrs::{ValueTypeAttrs, ValueVariantAttrs};
+
+/// Transform the input into a token stream containing the `FromArgValue`
+/// implementation, as well as all errors that occurred.
+pub(crate) fn impl_from_arg_value(input: &syn::DeriveInput) -> TokenStream {
+    let errors = &Errors::default();
+    let type_attrs = &ValueTypeAttrs::parse(errors, input);
+    let mut output_tokens = match &input.data {
+        syn::Data::Enum(de) => impl_from_arg_value_enum(errors, input, type_attrs, de),
+        syn::Data::Struct(ds) => impl_from_arg_value_newtype(errors, input, type_attrs, ds),
+        syn::Data::Union(_) => {
+            errors.err(input, "`#[derive(FromArgValue)]` cannot be applied to unions");
+            TokenStream::new()
+        }
+    };
+    errors.to_tokens(&mut output_tokens);
+    output_tokens
+}
+
+/// Implements `FromArgValue` for a fieldless enum, matching the values against
+/// the variant names and aliases.
+fn impl_from_arg_value_enum(
+    errors: &Errors,
+    input: &syn::DeriveInput,
+    type_attrs: &ValueTypeAttrs,
+    de: &syn::DataEnum,
+) -> TokenStream {
+    let case_insensitive = type_attrs.case_insensitive.is_some();
+    let mut seen_values = HashMap::new();
+    let mut possible_values = Vec::new();
+    let mut match_arms = Vec::new();
+
+    for variant in &de.variants {
+        if !matches!(variant.fields, syn::Fields::Unit) {
+            errors.err(
+                &variant.fields,
+                "`#[derive(FromArgValue)]` can only be applied to enums with unit variants",
+            );
+            continue;
+        }
+        let attrs = ValueVariantAttrs::parse(errors, variant);
+        let ident = &variant.ident;
+
+        let name = attrs
+            .name
+            .as_ref()
+            .map(LitStr::value)
+            .unwrap_or_else(|| to_kebab_case(&ident.to_string()));
+        let values: Vec<String> = std::iter::once(name.clone())
+            .chain(attrs.aliases.iter().map(LitStr::value))
+            .collect();
+
+        for value in &values {
+            let key = if case_insensitive {
+                value.to_ascii_lowercase()
+            } else {
+                value.clone()
+            };
+            if let Some(first) = seen_values.insert(key, ident) {
+                errors.err(
+                    variant,
+                    &format!("The value \"{}\" is already used by variant `{}`", value, first),
+                );
+            }
+        }
+
+        let conditions = values.iter().map(|value| {
+            if case_insensitive {
+                quote! { value.eq_ignore_ascii_case(#value) }
+            } else {
+                quote! { value == #value }
+            }
+        });
+        match_arms.push(quote! {
+            if #( #conditions )||* {
+                return ::std::result::Result::Ok(Self::#ident);
+            }
+        });
+        possible_values.push(name);
+    }
+
+    let msg = format!("must be one of: {}", possible_values.join(", "));
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics ::argp::FromArgValue for #name #ty_generics #where_clause {
+            const POSSIBLE_VALUES: &'static [&'static str] = &[ #( #possible_values, )* ];
+
+            fn from_arg_value(
+                value: &::std::ffi::OsStr,
+            ) -> ::std::result::Result<Self, ::std::string::String> {
+                let value = value.to_str().unwrap_or_default();
+                #( #match_arms )*
+                ::std::result::Result::Err(#msg.to_owned())
+            }
+        }
+    }
+}
+
+/// Implements `FromArgValue` for a struct with a single field, delegating to
+/// the type of the field.
+fn impl_from_arg_value_newtype(
+    errors: &Errors,
+    input: &syn::DeriveInput,
+    type_attrs: &ValueTypeAttrs,
+    ds: &syn::DataStruct,
+) -> TokenStream {
+    if let Some(case_insensitive) = &type_attrs.case_insensitive {
+        errors.err(case_insensitive, "`case_insensitive` may only be specified on enums");
+    }
+
+    let field = match ds.fields.iter().collect::<Vec<_>>()[..] {
+        [field] => field,
+        _ => {
+            errors.err(
+                &input.ident,
+                "`#[derive(FromArgValue)]` can only be applied to structs with exactly one field",
+            );
+            return TokenStream::new();
+        }
+    };
+    let member = match &field.ident {
+        Some(ident) => syn::Member::Named(ident.clone()),
+        None => syn::Member::Unnamed(0.into()),
+    };
+    let ty = &field.ty;
+
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .push(syn::parse_quote! { #ty: ::argp::FromArgValue });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics ::argp::FromArgValue for #name #ty_generics #where_clause {
+            const POSSIBLE_VALUES: &'static [&'static str] =
+                <#ty as ::argp::FromArgValue>::POSSIBLE_VALUES;
+
+            fn from_arg_value(
+                value: &::std::ffi::OsStr,
+            ) -> ::std::result::Result<Self, ::std::string::String> {
+                <#ty as ::argp::FromArgValue>::from_arg_value(value).map(|v| Self { #member: v })
+            }
+        }
+    }
+}
+
+/// Converts a variant name in CamelCase to kebab-case (e.g. `DryRun` to
+/// `dry-run`, `HTTPServer` to `http-server`).
+pub(crate) fn to_kebab_case(s: &str) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let mut res = String::with_capacity(s.len() + 4);
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            if prev.is_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_uppercase() && next_is_lower)
+            {
+                res.push('-');
+            }
+        }
+        if c == '_' {
+            res.push('-');
+        } else {
+            res.extend(c.to_lowercase());
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_to_kebab_case() {
+        assert_eq!(to_kebab_case("Debug"), "debug");
+        assert_eq!(to_kebab_case("DryRun"), "dry-run");
+        assert_eq!(to_kebab_case("HTTPServer"), "http-server");
+        assert_eq!(to_kebab_case("Ipv4"), "ipv4");
+        assert_eq!(to_kebab_case("Utf8Lossy"), "utf8-lossy");
+    }
+}
@@ -8,7 +8,7 @@ use syn::LitStr;
 
 use crate::errors::Errors;
 use crate::parse_attrs::{Description, FieldKind, TypeAttrs};
-use crate::{Optionality, StructField};
+use crate::{group_members, non_final_repeating_positional, Optionality, StructField};
 
 /// Returns a `TokenStream` generating an `argp::help::HelpInfo` instance.
 ///
@@ -20,15 +20,40 @@ pub(crate) fn inst_help_info(
     fields: &[StructField<'_>],
     subcommand: Option<&StructField<'_>>,
 ) -> TokenStream {
+    let non_final_repeating = non_final_repeating_positional(fields).map(|f| f.name);
     let positionals = fields
         .iter()
         .filter(|f| f.kind == FieldKind::Positional && !f.attrs.hidden_help)
-        .map(positional_info);
+        .map(|f| positional_info(f, Some(f.name) == non_final_repeating));
 
     let options = fields
         .iter()
         .filter(|f| f.long_name.is_some() && !f.attrs.hidden_help)
-        .map(|field| option_info(errors, field));
+        .map(|field| option_info(errors, field, group_or_option_usage(ty_attrs, fields, field)));
+
+    let flattened = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| f.kind == FieldKind::Flatten)
+        .map(|(i, field)| {
+            let preceding = &fields[..i];
+            let options_index = preceding
+                .iter()
+                .filter(|f| f.long_name.is_some() && !f.attrs.hidden_help)
+                .count();
+            let positionals_index = preceding
+                .iter()
+                .filter(|f| f.kind == FieldKind::Positional && !f.attrs.hidden_help)
+                .count();
+            let ty = field.ty_without_wrapper;
+            quote! {
+                ::argp::help::FlattenedHelpInfo {
+                    options_index: #options_index,
+                    positionals_index: #positionals_index,
+                    info: &<#ty as ::argp::CommandHelp>::HELP,
+                }
+            }
+        });
 
     let commands = if let Some(subcommand) = subcommand {
         let subcommand_ty = subcommand.ty_without_wrapper;
@@ -54,6 +79,53 @@ pub(crate) fn inst_help_info(
         quote! { ::std::option::Option::None }
     };
 
+    let version_option = ty_attrs.version.iter().map(|_| {
+        quote! {
+            ::argp::help::OptionArgInfo {
+                usage: "",
+                description: ("-V, --version", "Show version information and exit."),
+                global: false,
+                env: ::std::option::Option::None,
+                possible_values: &[],
+                aliases: &[],
+            }
+        }
+    });
+    let version = match &ty_attrs.version {
+        Some(syn::Meta::NameValue(m)) => {
+            let value = &m.value;
+            let value = match value {
+                syn::Expr::Path(path) => quote! { #path() },
+                lit => quote! { #lit },
+            };
+            quote! { ::std::option::Option::Some(|| ::std::convert::Into::into(#value)) }
+        }
+        Some(_) => quote! {
+            ::std::option::Option::Some(|| {
+                ::std::convert::Into::into(::std::env!("CARGO_PKG_VERSION"))
+            })
+        },
+        None => quote! { ::std::option::Option::None },
+    };
+
+    let help_short = ty_attrs.help_short_name();
+    let help_flags = match help_short {
+        Some(short) => format!("-{}, --help", short),
+        //           "-x, "
+        None => "    --help".to_owned(),
+    };
+    let help_short = match help_short {
+        Some(short) => {
+            let short = format!("-{}", short);
+            quote! { ::std::option::Option::Some(#short) }
+        }
+        None => quote! { ::std::option::Option::None },
+    };
+    let help_command = match ty_attrs.help_command_name() {
+        Some(name) => quote! { ::std::option::Option::Some(#name) },
+        None => quote! { ::std::option::Option::None },
+    };
+
     let description = require_description(errors, Span::call_site(), &ty_attrs.description, "type");
     let footer = ty_attrs
         .footer
@@ -66,19 +138,27 @@ pub(crate) fn inst_help_info(
         ::argp::help::HelpInfo {
             description: #description,
             positionals: &[ #( #positionals, )* ],
-            options: &[ #( #options, )* ],
+            options: &[ #( #options, )* #( #version_option, )* ],
             commands: #commands,
             footer: #footer,
+            flattened: &[ #( #flattened, )* ],
+            version: #version,
+            help_short: #help_short,
+            help_command: #help_command,
+            help_option: ::argp::help::help_opt(#help_flags),
         }
     }
 }
 
 /// Generates an usage string for the given positional argument
 /// (e.g. `[<foo>...]`).
-fn positional_usage(field: &StructField<'_>) -> String {
+///
+/// - `required`: Whether the argument is required even though it's repeating.
+fn positional_usage(field: &StructField<'_>, required: bool) -> String {
+    let optional = !field.optionality.is_required() && !required;
     let mut out = String::new();
 
-    if !field.optionality.is_required() {
+    if optional {
         out.push('[');
     }
     if field.attrs.greedy.is_none() {
@@ -93,22 +173,64 @@ fn positional_usage(field: &StructField<'_>) -> String {
     if field.attrs.greedy.is_none() {
         out.push('>');
     }
-    if !field.optionality.is_required() {
+    if optional {
         out.push(']');
     }
 
     out
 }
 
+/// Formats an usage word for the given option, or for the whole group if the
+/// option is the first visible member of a group (e.g. `(--json | --yaml)`).
+/// Returns an empty string for the other members of a group.
+fn group_or_option_usage(
+    ty_attrs: &TypeAttrs,
+    fields: &[StructField<'_>],
+    field: &StructField<'_>,
+) -> String {
+    let group = field.attrs.group.as_ref().and_then(|name| {
+        ty_attrs
+            .groups
+            .iter()
+            .find(|group| group.name.value() == name.value())
+    });
+    let group = match group {
+        Some(group) => group,
+        None => return option_usage(field),
+    };
+
+    let members: Vec<_> = group_members(fields, group)
+        .filter(|f| !f.attrs.hidden_help)
+        .collect();
+    if members.first().map(|first| first.name) != Some(field.name) {
+        return String::new();
+    }
+
+    let (open, close) = if group.required {
+        ('(', ')')
+    } else {
+        ('[', ']')
+    };
+    let words: Vec<_> = members.iter().map(|f| option_usage_words(f)).collect();
+
+    format!("{}{}{}", open, words.join(" | "), close)
+}
+
 /// Formats an usage word for the given option (e.g. `[-f <foo>]`). This
 /// function must only be called on options (things with `long_name.is_some()`)
 fn option_usage(field: &StructField<'_>) -> String {
-    let mut out = String::new();
-
     // bookend with `[` and `]` if optional
-    if !field.optionality.is_required() {
-        out.push('[');
+    if field.optionality.is_required() {
+        option_usage_words(field)
+    } else {
+        format!("[{}]", option_usage_words(field))
     }
+}
+
+/// Formats the given option without the brackets (e.g. `-f <foo>`). This
+/// function must only be called on options (things with `long_name.is_some()`)
+fn option_usage_words(field: &StructField<'_>) -> String {
+    let mut out = String::new();
 
     let long_name = field
         .long_name
@@ -119,33 +241,86 @@ fn option_usage(field: &StructField<'_>) -> String {
         out.push('-');
         out.push(short.value());
     } else {
-        out.push_str(long_name);
+        out.push_str(&long_name_usage(field));
     }
 
     match field.kind {
-        FieldKind::SubCommand | FieldKind::Positional => unreachable!(), // don't have long_name
+        FieldKind::SubCommand | FieldKind::Positional | FieldKind::Flatten => unreachable!(), // don't have long_name
         FieldKind::Switch => {}
         FieldKind::Option => {
-            out.push_str(" <");
-            if let Some(arg_name) = &field.attrs.arg_name {
-                out.push_str(&arg_name.value());
+            let arg_name = if let Some(arg_name) = &field.attrs.arg_name {
+                arg_name.value()
             } else {
-                out.push_str(long_name.trim_start_matches("--"));
-            }
-            if field.optionality == Optionality::Repeating {
-                out.push_str("...");
+                long_name.trim_start_matches("--").to_owned()
+            };
+            if field.attrs.default_missing.is_some() {
+                out.push_str(&optional_value_usage(&arg_name));
+                if field.optionality == Optionality::Repeating {
+                    out.push_str("...");
+                }
+            } else if let Some(value_tys) = &field.multi_value_tys {
+                out.push(' ');
+                out.push_str(&multi_value_usage(&arg_name, value_tys.len()));
+                if field.optionality == Optionality::Repeating {
+                    out.push_str("...");
+                }
+            } else if let Some(delimiter) = &field.attrs.delimiter {
+                out.push(' ');
+                out.push_str(&delimited_usage(&arg_name, delimiter.value()));
+            } else {
+                out.push(' ');
+                out.push('<');
+                out.push_str(&arg_name);
+                if field.optionality == Optionality::Repeating {
+                    out.push_str("...");
+                }
+                out.push('>');
             }
-            out.push('>');
         }
     }
 
-    if !field.optionality.is_required() {
-        out.push(']');
-    }
-
     out
 }
 
+/// Formats the values of an option that takes `arity` values (e.g. `<old>
+/// <new>`). The `arg_name` contains either a single name used for all the
+/// values, or `arity` names separated by whitespace.
+fn multi_value_usage(arg_name: &str, arity: usize) -> String {
+    let names: Vec<&str> = arg_name.split_whitespace().collect();
+
+    (0..arity)
+        .map(|i| format!("<{}>", names.get(i).or(names.first()).unwrap_or(&arg_name)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Formats the value of an option that may be provided without it (e.g.
+/// `[=<when>]`).
+fn optional_value_usage(arg_name: &str) -> String {
+    format!("[=<{}>]", arg_name)
+}
+
+/// Formats the value of an option with a delimiter (e.g. `<tag>[,<tag>...]`).
+fn delimited_usage(arg_name: &str, delimiter: char) -> String {
+    format!("<{0}>[{1}<{0}>...]", arg_name, delimiter)
+}
+
+/// Formats the long name of the given option, including the optional `no-`
+/// prefix for negatable switches (e.g. `--[no-]foo`). This function must only
+/// be called on options (things with `long_name.is_some()`)
+fn long_name_usage(field: &StructField<'_>) -> String {
+    let long_name = field
+        .long_name
+        .as_ref()
+        .expect("missing long name for option");
+
+    if field.negated_long_name.is_some() {
+        long_name.replacen("--", "--[no-]", 1)
+    } else {
+        long_name.clone()
+    }
+}
+
 // TODO(cramertj) make it so this is only called at least once per object so
 // as to avoid creating multiple errors.
 pub fn require_description(
@@ -167,8 +342,41 @@ Add a doc comment or an `#[argp(description = \"...\")]` attribute.",
     })
 }
 
-fn positional_info(field: &StructField<'_>) -> TokenStream {
-    let usage = positional_usage(field);
+/// Appends the range of the field's values to the description (e.g.
+/// `[1..=256]`). The possible values are appended at runtime.
+fn append_value_constraints(description: &mut String, field: &StructField<'_>) {
+    if let Some(range) = &field.attrs.range {
+        if !description.is_empty() {
+            description.push(' ');
+        }
+        description.push('[');
+        description.push_str(&range.value());
+        description.push(']');
+    }
+}
+
+/// Returns the values accepted by the given option or positional argument, from
+/// the `possible_values` attribute or the `FromArgValue` implementation.
+pub(crate) fn possible_values(field: &StructField<'_>) -> TokenStream {
+    let attrs = &field.attrs;
+
+    if let Some(ident) = &field.possible_values_const {
+        quote! { #ident }
+    } else if field.kind == FieldKind::Switch
+        || attrs.greedy.is_some()
+        || attrs.from_str_fn.is_some()
+        || attrs.from_os_str_fn.is_some()
+        || field.multi_value_tys.is_some()
+    {
+        quote! { &[] }
+    } else {
+        let ty = field.ty_without_wrapper;
+        quote! { <#ty as ::argp::FromArgValue>::POSSIBLE_VALUES }
+    }
+}
+
+fn positional_info(field: &StructField<'_>, required: bool) -> TokenStream {
+    let usage = positional_usage(field, required);
 
     let mut field_name = String::new();
     let mut description = String::new();
@@ -180,25 +388,26 @@ fn positional_info(field: &StructField<'_>) -> TokenStream {
         if let Some(desc) = &field.attrs.description {
             description = desc.to_string()
         }
+        append_value_constraints(&mut description, field);
     }
 
+    let possible_values = possible_values(field);
+
     quote! {
         ::argp::help::OptionArgInfo {
             usage: #usage,
             description: (#field_name, #description),
             global: false,
+            env: ::std::option::Option::None,
+            possible_values: #possible_values,
+            aliases: &[],
         }
     }
 }
 
-fn option_info(errors: &Errors, field: &StructField<'_>) -> TokenStream {
-    let usage = option_usage(field);
-
+fn option_info(errors: &Errors, field: &StructField<'_>, usage: String) -> TokenStream {
     let short = field.attrs.short.as_ref().map(|s| s.value());
-    let long_with_leading_dashes = field
-        .long_name
-        .as_ref()
-        .expect("missing long name for option");
+    let long_with_leading_dashes = long_name_usage(field);
 
     let arg_name = if field.kind == FieldKind::Option {
         Some(field.positional_arg_name())
@@ -216,24 +425,48 @@ fn option_info(errors: &Errors, field: &StructField<'_>) -> TokenStream {
         //             "-x, "
         flags.push_str("    ");
     }
-    flags.push_str(long_with_leading_dashes);
+    flags.push_str(&long_with_leading_dashes);
 
     if let Some(arg_name) = arg_name {
-        flags.push_str(" <");
-        flags.push_str(&arg_name);
-        flags.push('>');
+        if field.attrs.default_missing.is_some() {
+            flags.push_str(&optional_value_usage(&arg_name));
+        } else if let Some(value_tys) = &field.multi_value_tys {
+            flags.push(' ');
+            flags.push_str(&multi_value_usage(&arg_name, value_tys.len()));
+        } else if let Some(delimiter) = &field.attrs.delimiter {
+            flags.push(' ');
+            flags.push_str(&delimited_usage(&arg_name, delimiter.value()));
+        } else {
+            flags.push(' ');
+            flags.push('<');
+            flags.push_str(&arg_name);
+            flags.push('>');
+        }
     }
 
-    let description =
+    let mut description =
         require_description(errors, field.name.span(), &field.attrs.description, "field");
+    append_value_constraints(&mut description, field);
 
     let global = field.attrs.global;
 
+    let env = match &field.env_name {
+        Some(env_name) => quote! { ::std::option::Option::Some(#env_name) },
+        None => quote! { ::std::option::Option::None },
+    };
+
+    let possible_values = possible_values(field);
+
+    let aliases = &field.attrs.aliases;
+
     quote! {
         ::argp::help::OptionArgInfo {
             usage: #usage,
             description: (#flags, #description),
             global: #global,
+            env: #env,
+            possible_values: #possible_values,
+            aliases: &[ #( #aliases, )* ],
         }
     }
 }
@@ -8,21 +8,21 @@
 //! For more thorough documentation, see the `argp` crate itself.
 
 mod errors;
+mod from_arg_value;
 mod help;
 mod markdown;
 mod parse_attrs;
 
-use std::collections::HashMap;
-use std::iter;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
 use proc_macro2::{Span, TokenStream};
 use quote::{quote, quote_spanned, ToTokens};
-use syn::spanned::Spanned;
+use syn::ext::IdentExt;
 use syn::{GenericArgument, LitStr, PathArguments, Type};
 
 use crate::errors::Errors;
-use crate::parse_attrs::{check_long_name, FieldAttrs, FieldKind, TypeAttrs};
+use crate::parse_attrs::{check_long_name, FieldAttrs, FieldKind, GroupAttrs, TypeAttrs};
 
 /// Entrypoint for `#[derive(FromArgs)]`.
 #[proc_macro_derive(FromArgs, attributes(argp))]
@@ -32,6 +32,14 @@ pub fn argp_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     gen.into()
 }
 
+/// Entrypoint for `#[derive(FromArgValue)]`.
+#[proc_macro_derive(FromArgValue, attributes(argp))]
+pub fn argp_derive_from_arg_value(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+    let gen = from_arg_value::impl_from_arg_value(&ast);
+    gen.into()
+}
+
 /// Transform the input into a token stream containing any generated implementations,
 /// as well as all errors that occurred.
 fn impl_from_args(input: &syn::DeriveInput) -> TokenStream {
@@ -101,12 +109,28 @@ struct StructField<'a> {
     optionality: Optionality,
     // The `--`-prefixed name of the option, if one exists.
     long_name: Option<String>,
+    // The `--no-`-prefixed name of the negatable switch, if one exists.
+    negated_long_name: Option<String>,
+    // The name of the environment variable to fall back to, if one exists.
+    env_name: Option<String>,
+    // The types of the values of an option that takes a fixed number of values
+    // per occurrence, i.e. the element types of a tuple or an array.
+    multi_value_tys: Option<Vec<&'a syn::Type>>,
+    // The name of the constant with the values of the `possible_values`
+    // attribute, if specified. It's used for both parsing and the help message.
+    possible_values_const: Option<syn::Ident>,
 }
 
 impl<'a> StructField<'a> {
     /// Attempts to parse a field of a `#[derive(FromArgs)]` struct, pulling out the
     /// fields required for code generation.
-    fn new(errors: &Errors, field: &'a syn::Field, attrs: FieldAttrs) -> Option<Self> {
+    fn new(
+        errors: &Errors,
+        struct_name: &syn::Ident,
+        field: &'a syn::Field,
+        attrs: FieldAttrs,
+        type_attrs: &TypeAttrs,
+    ) -> Option<Self> {
         let name = field.ident.as_ref().expect("missing ident for named field");
 
         // Ensure that one "kind" is present (switch, option, subcommand, positional)
@@ -117,7 +141,8 @@ impl<'a> StructField<'a> {
                 field,
                 concat!(
                     "Missing `argp` field kind attribute.\n",
-                    "Expected one of: `switch`, `option`, `remaining`, `subcommand`, `positional`",
+                    "Expected one of: `switch`, `option`, `remaining`, `subcommand`, `positional`, ",
+                    "`flatten`",
                 ),
             );
             return None;
@@ -176,6 +201,48 @@ impl<'a> StructField<'a> {
                 };
                 ty_without_wrapper = inner.unwrap_or(&field.ty);
             }
+            FieldKind::Flatten => {
+                optionality = Optionality::None;
+                ty_without_wrapper = &field.ty;
+            }
+        }
+
+        if let (Some(delimiter), false) = (&attrs.delimiter, optionality == Optionality::Repeating)
+        {
+            errors.err(delimiter, "`delimiter` may only be specified on `Vec` fields");
+        }
+
+        // Infer the number of values of an option from a tuple or an array type.
+        let multi_value_tys = match kind {
+            FieldKind::Option => ty_multi_value_elems(errors, ty_without_wrapper),
+            _ => None,
+        };
+        if let Some(value_tys) = &multi_value_tys {
+            if let Some(default_missing) = &attrs.default_missing {
+                errors.err(
+                    default_missing,
+                    "`default_missing` cannot be specified on options with multiple values",
+                );
+            }
+            if let Some(delimiter) = &attrs.delimiter {
+                errors.err(
+                    delimiter,
+                    "`delimiter` cannot be specified on options with multiple values",
+                );
+            }
+            if let Some(arg_name) = &attrs.arg_name {
+                let count = arg_name.value().split_whitespace().count();
+                if count > 1 && count != value_tys.len() {
+                    errors.err(
+                        arg_name,
+                        &format!(
+                            "`arg_name` must contain either one name or {} names separated by \
+                             whitespace",
+                            value_tys.len()
+                        ),
+                    );
+                }
+            }
         }
 
         // Determine the "long" name of options and switches.
@@ -191,15 +258,43 @@ impl<'a> StructField<'a> {
                         check_long_name(errors, name, &kebab_name);
                         kebab_name
                     });
-                if long_name == "help" {
-                    errors.err(field, "Custom `--help` flags are not supported.");
+                ensure_not_help_option(errors, field, type_attrs, &attrs, &long_name);
+                if type_attrs.version.is_some() {
+                    ensure_not_version_option(errors, field, &attrs, &long_name);
                 }
                 let long_name = format!("--{}", long_name);
                 Some(long_name)
             }
-            FieldKind::SubCommand | FieldKind::Positional => None,
+            FieldKind::SubCommand | FieldKind::Positional | FieldKind::Flatten => None,
         };
 
+        let negated_long_name = match (&long_name, &attrs.negatable) {
+            (Some(long_name), Some(_)) => Some(long_name.replacen("--", "--no-", 1)),
+            _ => None,
+        };
+
+        // Determine the name of the environment variable. Defaults to the
+        // upper-case'd long name prefixed with `env_prefix`, if specified.
+        let env_name = match (&long_name, &attrs.env, &type_attrs.env_prefix) {
+            (Some(_), Some(env), _) => Some(env.value()),
+            (Some(long_name), None, Some(prefix)) => Some(format!(
+                "{}{}",
+                prefix.value(),
+                long_name
+                    .trim_start_matches("--")
+                    .to_uppercase()
+                    .replace('-', "_")
+            )),
+            _ => None,
+        };
+
+        let possible_values_const = attrs.possible_values.as_ref().map(|_| {
+            syn::Ident::new(
+                &format!("__ARGP_POSSIBLE_VALUES_{}_{}", struct_name.unraw(), name.unraw()),
+                Span::call_site(),
+            )
+        });
+
         Some(StructField {
             field,
             attrs,
@@ -208,6 +303,10 @@ impl<'a> StructField<'a> {
             ty_without_wrapper,
             name,
             long_name,
+            negated_long_name,
+            env_name,
+            multi_value_tys,
+            possible_values_const,
         })
     }
 
@@ -220,6 +319,73 @@ impl<'a> StructField<'a> {
     }
 }
 
+/// Ensures that the option doesn't use the names of the help option (see
+/// `#[argp(help_short = ...)]`), neither as an alias.
+fn ensure_not_help_option(
+    errors: &Errors,
+    field: &syn::Field,
+    type_attrs: &TypeAttrs,
+    attrs: &FieldAttrs,
+    long_name: &str,
+) {
+    if long_name == "help" {
+        errors.err(field, "Custom `--help` flags are not supported.");
+    }
+    let help_short = type_attrs.help_short_name();
+
+    if let Some(short) = &attrs.short {
+        if Some(short.value()) == help_short {
+            errors.err(
+                short,
+                &format!(
+                    "The short name `-{}` is already used by the help option.\n\
+                     Rename or disable it with `#[argp(help_short = ...)]`.",
+                    short.value()
+                ),
+            );
+        }
+    }
+    for alias in attrs.aliases.iter().chain(&attrs.hidden_aliases) {
+        let value = alias.value();
+        if value == "--help" {
+            errors.err(alias, "Custom `--help` flags are not supported.");
+        } else if help_short.is_some_and(|short| value == format!("-{}", short)) {
+            errors.err(
+                alias,
+                &format!(
+                    "The alias `{}` is already used by the help option.\n\
+                     Rename or disable it with `#[argp(help_short = ...)]`.",
+                    value
+                ),
+            );
+        }
+    }
+}
+
+/// Ensures that the option doesn't use the names of the `-V, --version` option
+/// added by `#[argp(version)]`, neither as an alias.
+fn ensure_not_version_option(
+    errors: &Errors,
+    field: &syn::Field,
+    attrs: &FieldAttrs,
+    long_name: &str,
+) {
+    if long_name == "version" {
+        errors.err(field, "Custom `--version` flags cannot be used with `#[argp(version)]`.");
+    }
+    if let Some(short) = attrs.short.as_ref().filter(|short| short.value() == 'V') {
+        errors.err(short, "The short name `-V` is already used by `#[argp(version)]`.");
+    }
+    for alias in attrs.aliases.iter().chain(&attrs.hidden_aliases) {
+        if matches!(alias.value().as_str(), "-V" | "--version") {
+            errors.err(
+                alias,
+                &format!("The alias `{}` is already used by `#[argp(version)]`.", alias.value()),
+            );
+        }
+    }
+}
+
 fn to_kebab_case(s: &str) -> String {
     let words = s.split('_').filter(|word| !word.is_empty());
     let mut res = String::with_capacity(s.len());
@@ -260,14 +426,18 @@ fn impl_from_args_struct(
         .iter()
         .filter_map(|field| {
             let attrs = FieldAttrs::parse(errors, field);
-            StructField::new(errors, field, attrs)
+            StructField::new(errors, name, field, attrs, type_attrs)
         })
         .collect();
 
     ensure_unique_names(errors, &fields);
-    ensure_only_last_positional_is_optional(errors, &fields);
+    ensure_valid_positionals_optionality(errors, &fields);
     ensure_only_one_subcommand(errors, &fields);
-    ensure_global_only_when_subcommand(errors, &fields);
+    ensure_valid_flatten_only(errors, type_attrs, &fields);
+    ensure_valid_conflicts_and_groups(errors, type_attrs, &fields);
+    ensure_valid_requirements(errors, &fields);
+
+    let generic_args = &with_inferred_bounds(generic_args, struct_field_bounds(&fields));
 
     let subcommand = fields
         .iter()
@@ -275,19 +445,122 @@ fn impl_from_args_struct(
 
     let impl_span = Span::call_site();
 
-    let from_args_method = impl_from_args_struct_from_args(&fields, subcommand);
+    let from_flattened_method =
+        impl_from_args_struct_from_flattened(type_attrs, &fields, subcommand);
+
+    let abbreviations = type_attrs.abbreviations.is_some();
+    // The expansion is emitted only if enabled to not increase the size of
+    // binaries that don't use it.
+    let response_files = if type_attrs.response_files.is_some() {
+        quote! { ::std::option::Option::Some(|__args| ::argp::expand_response_files(__args)) }
+    } else {
+        quote! { ::std::option::Option::None }
+    };
+    let options_first = type_attrs.options_first.is_some();
+    let suggestions = type_attrs.suggestions.is_some();
+
+    let flattened_tys: Vec<_> = fields
+        .iter()
+        .filter(|field| field.kind == FieldKind::Flatten)
+        .map(|field| field.ty_without_wrapper)
+        .collect();
+    let arg_names = flag_str_to_output_table_map_entries(&fields)
+        .into_iter()
+        .map(|(name, _)| name);
+
+    let has_global = fields.iter().any(|field| field.attrs.global);
+    let has_subcommand = subcommand.is_some();
+
+    // Duplicate names across the flattened structs, flattened structs with
+    // a subcommand, and global options of flattened structs in a command
+    // without a subcommand can be detected at compile time only for
+    // non-generic structs, the other ones are checked by a debug assertion
+    // when parsing.
+    let ensure_valid_flattened = if !flattened_tys.is_empty() && generic_args.params.is_empty() {
+        let ensure_no_global = if subcommand.is_none() && type_attrs.flatten.is_none() {
+            quote! {
+                const _: () = ::std::assert!(
+                    !<#name as ::argp::parser::Flatten>::HAS_GLOBAL,
+                    "`global` may only be used when the struct contains a `#[argp(subcommand)]` field"
+                );
+            }
+        } else {
+            TokenStream::new()
+        };
+        quote! {
+            const _: () = <#name as ::argp::parser::Flatten>::ARG_NAMES.ensure_unique();
+            #(
+                const _: () = ::std::assert!(
+                    !<#flattened_tys as ::argp::parser::Flatten>::HAS_SUBCOMMAND,
+                    "A flattened struct cannot have a subcommand"
+                );
+            )*
+            #ensure_no_global
+        }
+    } else {
+        TokenStream::new()
+    };
 
     let help_struct = help::inst_help_info(errors, type_attrs, &fields, subcommand);
 
+    let ensure_valid_required_if_values = if generic_args.params.is_empty() {
+        ensure_valid_required_if_values(&fields)
+    } else {
+        TokenStream::new()
+    };
+
+    let possible_values_consts = fields.iter().filter_map(|field| {
+        let ident = field.possible_values_const.as_ref()?;
+        let values = field.attrs.possible_values.as_ref()?;
+        Some(quote! {
+            #[allow(non_upper_case_globals)]
+            const #ident: &[&str] = &[ #( #values, )* ];
+        })
+    });
+
     let top_or_sub_cmd_impl = top_or_sub_cmd_impl(errors, name, type_attrs, generic_args);
 
     let (impl_generics, ty_generics, where_clause) = generic_args.split_for_impl();
     let trait_impl = quote_spanned! { impl_span =>
         #[automatically_derived]
         impl #impl_generics ::argp::FromArgs for #name #ty_generics #where_clause {
-            #from_args_method
+            fn _from_args(__cmd_name: &[&str], __args: &[&::std::ffi::OsStr], __parent: ::std::option::Option<&mut dyn ::argp::parser::ParseGlobalOptions>)
+                -> ::std::result::Result<Self, ::argp::EarlyExit>
+            {
+                <Self as ::argp::parser::Flatten>::_from_flattened(|__fragment| {
+                    ::argp::parser::parse_struct_args(
+                        __cmd_name,
+                        __args,
+                        __fragment,
+                        &<Self as ::argp::CommandHelp>::HELP,
+                        #response_files,
+                        #abbreviations,
+                        #options_first,
+                        #suggestions,
+                        __parent,
+                    )
+                })
+            }
         }
 
+        #[automatically_derived]
+        impl #impl_generics ::argp::parser::Flatten for #name #ty_generics #where_clause {
+            const ARG_NAMES: ::argp::parser::ArgNames = ::argp::parser::ArgNames {
+                names: &[ #( #arg_names, )* ],
+                flattened: &[ #( &<#flattened_tys as ::argp::parser::Flatten>::ARG_NAMES, )* ],
+            };
+            const HAS_SUBCOMMAND: bool = #has_subcommand;
+            const HAS_GLOBAL: bool = #has_global
+                #( || <#flattened_tys as ::argp::parser::Flatten>::HAS_GLOBAL )*;
+
+            #from_flattened_method
+        }
+
+        #ensure_valid_flattened
+        #ensure_valid_required_if_values
+
+        #( #possible_values_consts )*
+
         #[automatically_derived]
         impl #impl_generics ::argp::CommandHelp for #name #ty_generics #where_clause {
             const HELP: ::argp::help::HelpInfo = #help_struct;
@@ -299,7 +572,8 @@ fn impl_from_args_struct(
     trait_impl
 }
 
-fn impl_from_args_struct_from_args<'a>(
+fn impl_from_args_struct_from_flattened<'a>(
+    type_attrs: &TypeAttrs,
     fields: &'a [StructField<'a>],
     subcommand: Option<&StructField<'_>>,
 ) -> TokenStream {
@@ -311,10 +585,16 @@ fn impl_from_args_struct_from_args<'a>(
         .collect();
     let positional_field_idents = positional_fields.iter().map(|field| &field.field.ident);
     let positional_field_names = positional_fields.iter().map(|field| field.name.to_string());
-    let last_positional_is_repeating = positional_fields
-        .last()
-        .map(|field| field.optionality == Optionality::Repeating)
-        .unwrap_or(false);
+    let positional_allow_hyphen_values = positional_fields
+        .iter()
+        .map(|field| field.attrs.allow_hyphen_values.is_some());
+    let repeating_positional = match positional_fields
+        .iter()
+        .position(|field| field.optionality == Optionality::Repeating)
+    {
+        Some(index) => quote! { ::std::option::Option::Some(#index) },
+        None => quote! { ::std::option::Option::None },
+    };
     let last_positional_is_greedy = positional_fields
         .last()
         .map(|field| field.kind == FieldKind::Positional && field.attrs.greedy.is_some())
@@ -329,24 +609,76 @@ fn impl_from_args_struct_from_args<'a>(
             FieldKind::Switch => {
                 Some(quote! { ::argp::parser::ParseStructOption::Flag(&mut #field_name) })
             }
-            FieldKind::SubCommand | FieldKind::Positional => None,
+            FieldKind::SubCommand | FieldKind::Positional | FieldKind::Flatten => None,
         }
     });
 
     let flag_global_table = fields.iter().filter_map(|field| match field.kind {
         FieldKind::Option | FieldKind::Switch => Some(field.attrs.global),
-        FieldKind::SubCommand | FieldKind::Positional => None,
+        FieldKind::SubCommand | FieldKind::Positional | FieldKind::Flatten => None,
+    });
+
+    // The tables (and the code handling them) are emitted only if used by
+    // some field to not increase the size of binaries that don't use them.
+    let flag_env_table = optional_flag_table(fields, |field| {
+        field
+            .env_name
+            .as_ref()
+            .map(|env_name| quote! { ::std::option::Option::Some(#env_name) })
+    });
+    let fill_from_env = if fields.iter().any(|field| field.env_name.is_some()) {
+        quote! { ::std::option::Option::Some(::argp::parser::fill_from_env) }
+    } else {
+        quote! { ::std::option::Option::None }
+    };
+
+    let flag_default_missing_table = optional_flag_table(fields, |field| {
+        field
+            .attrs
+            .default_missing
+            .as_ref()
+            .map(|default_missing| quote! { ::std::option::Option::Some(#default_missing) })
+    });
+
+    let flag_overridable_table = optional_flag_table(fields, |field| {
+        (field.attrs.overridable.is_some() || type_attrs.overridable.is_some())
+            .then(|| quote! { true })
     });
 
-    let flag_str_to_output_table_map = flag_str_to_output_table_map_entries(fields);
+    let flag_count = fields
+        .iter()
+        .filter(|field| matches!(field.kind, FieldKind::Option | FieldKind::Switch))
+        .count();
+
+    let flag_str_to_output_table_map = flag_str_to_output_table_map_entries(fields)
+        .into_iter()
+        .map(|(name, i)| quote! { (#name, #i) });
 
     let impl_span = Span::call_site();
 
+    let slots_provided_ident = syn::Ident::new("__slots_provided", impl_span);
+
+    let conflicting_slots = conflicting_slots(type_attrs, fields);
+
+    let check_conflicts = check_conflicts(&slots_provided_ident, &conflicting_slots, fields);
+
+    let slots_conflicts_table = conflicting_slots.iter().map(|(a, b)| quote! { (#a, #b) });
+
     let missing_requirements_ident = syn::Ident::new("__missing_requirements", impl_span);
 
     let append_missing_requirements =
         append_missing_requirements(&missing_requirements_ident, fields);
 
+    let append_missing_groups = append_missing_groups(
+        &missing_requirements_ident,
+        &slots_provided_ident,
+        type_attrs,
+        fields,
+    );
+
+    let append_conditional_requirements =
+        append_conditional_requirements(&missing_requirements_ident, &slots_provided_ident, fields);
+
     let parse_subcommands = if let Some(subcommand) = subcommand {
         let name = subcommand.name;
         let ty = subcommand.ty_without_wrapper;
@@ -364,44 +696,60 @@ fn impl_from_args_struct_from_args<'a>(
         quote_spanned! { impl_span => ::std::option::Option::None }
     };
 
+    let fragment = quote_spanned! { impl_span =>
+        ::argp::parser::ParseStructFragment {
+            arg_to_slot: &[ #( #flag_str_to_output_table_map ,)* ],
+            slots: &mut [ #( #flag_output_table, )* ],
+            slots_global: &[ #( #flag_global_table, )* ],
+            slots_env: #flag_env_table,
+            slots_default_missing: #flag_default_missing_table,
+            slots_overridable: #flag_overridable_table,
+            slots_conflicts: &[ #( #slots_conflicts_table, )* ],
+            slots_provided: &mut #slots_provided_ident,
+            fill_from_env: #fill_from_env,
+            positionals: &mut [
+                #(
+                    ::argp::parser::ParseStructPositional {
+                        name: #positional_field_names,
+                        slot: &mut #positional_field_idents as &mut dyn ::argp::parser::ParseValueSlot,
+                        allow_hyphen_values: #positional_allow_hyphen_values,
+                    },
+                )*
+            ],
+            repeating_positional: #repeating_positional,
+            last_positional_is_greedy: #last_positional_is_greedy,
+            subcommand: #parse_subcommands,
+        }
+    };
+
+    let parse_fragments = parse_flattened_fragments(fields, fragment);
+
     let method_impl = quote_spanned! { impl_span =>
-        fn _from_args(__cmd_name: &[&str], __args: &[&::std::ffi::OsStr], __parent: ::std::option::Option<&mut dyn ::argp::parser::ParseGlobalOptions>)
-            -> ::std::result::Result<Self, ::argp::EarlyExit>
+        fn _from_flattened(
+            __parse: impl ::std::ops::FnOnce(::argp::parser::ParseStructFragment<'_>)
+                -> ::std::result::Result<(), ::argp::EarlyExit>,
+        ) -> ::std::result::Result<Self, ::argp::EarlyExit>
         {
             #![allow(clippy::unwrap_in_result)]
 
             #( #init_fields )*
 
-            ::argp::parser::parse_struct_args(
-                __cmd_name,
-                __args,
-                ::argp::parser::ParseStructOptions {
-                    arg_to_slot: &[ #( #flag_str_to_output_table_map ,)* ],
-                    slots: &mut [ #( #flag_output_table, )* ],
-                    slots_global: &[ #( #flag_global_table, )* ],
-                    help: &<Self as argp::CommandHelp>::HELP,
-                    parent: __parent,
-                },
-                ::argp::parser::ParseStructPositionals {
-                    positionals: &mut [
-                        #(
-                            ::argp::parser::ParseStructPositional {
-                                name: #positional_field_names,
-                                slot: &mut #positional_field_idents as &mut dyn ::argp::parser::ParseValueSlot,
-                            },
-                        )*
-                    ],
-                    last_is_repeating: #last_positional_is_repeating,
-                    last_is_greedy: #last_positional_is_greedy,
-                },
-                #parse_subcommands,
-                &<Self as ::argp::CommandHelp>::HELP,
-            )?;
+            let mut #slots_provided_ident = [false; #flag_count];
+
+            #parse_fragments
+
+            #( #check_conflicts )*
 
             let mut #missing_requirements_ident = ::argp::MissingRequirements::default();
             #(
                 #append_missing_requirements
             )*
+            #(
+                #append_missing_groups
+            )*
+            #(
+                #append_conditional_requirements
+            )*
             #missing_requirements_ident.err_on_any()?;
 
             ::std::result::Result::Ok(Self {
@@ -413,23 +761,299 @@ fn impl_from_args_struct_from_args<'a>(
     method_impl
 }
 
-/// Ensures that only the last positional arg is non-required.
-fn ensure_only_last_positional_is_optional(errors: &Errors, fields: &[StructField<'_>]) {
-    let mut first_non_required_span = None;
+/// Returns statements that create the parsing tables of the flattened fields
+/// (using `Flatten::_from_flattened`), merge them with the own `fragment` of
+/// the struct and pass the result to the `__parse` function.
+///
+/// The fragments are merged starting from the last flattened field, so the
+/// positional arguments of each flattened struct can be inserted at the index
+/// of the field among the own positional arguments.
+fn parse_flattened_fragments(fields: &[StructField<'_>], fragment: TokenStream) -> TokenStream {
+    let flattened: Vec<_> = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| field.kind == FieldKind::Flatten)
+        .map(|(i, field)| {
+            let positionals_index = fields[..i]
+                .iter()
+                .filter(|field| field.kind == FieldKind::Positional)
+                .count();
+            let ident = syn::Ident::new(&format!("__flattened_{}", field.name), Span::call_site());
+            (field, ident, positionals_index)
+        })
+        .collect();
+
+    if flattened.is_empty() {
+        return quote! { __parse(#fragment)?; };
+    }
+
+    let mut merge = quote! { __parse(__fragment) };
+    for (_, ident, positionals_index) in &flattened {
+        merge = quote! {
+            __fragment.flatten(#ident, #positionals_index, |__fragment| #merge)
+        };
+    }
+    let mut parse = quote! {
+        let __fragment = #fragment;
+        #merge
+    };
+    for (i, (field, ident, _)) in flattened.iter().enumerate().rev() {
+        let name = field.name;
+        let ty = field.ty_without_wrapper;
+        // All but the first one are nested in the closure of the previous one.
+        let ok = (i > 0).then(|| quote! { ::std::result::Result::Ok(()) });
+        parse = quote! {
+            #name = ::std::option::Option::Some(
+                <#ty as ::argp::parser::Flatten>::_from_flattened(|#ident| { #parse })?
+            );
+            #ok
+        };
+    }
+    parse
+}
+
+/// Ensures that only the last positional arg is non-required, except for one
+/// repeating positional arg that may be followed by required ones (e.g.
+/// `<source>... <dest>`).
+fn ensure_valid_positionals_optionality(errors: &Errors, fields: &[StructField<'_>]) {
+    let positionals: Vec<_> = fields
+        .iter()
+        .filter(|field| field.kind == FieldKind::Positional)
+        .collect();
+    let mut repeating: Option<&StructField<'_>> = None;
+
+    for (i, field) in positionals.iter().enumerate() {
+        let next = positionals.get(i + 1);
+        match field.optionality {
+            Optionality::None => {}
+            Optionality::Repeating => {
+                if let Some(first) = repeating {
+                    errors.err(&first.field, "Only one positional argument may be `Vec`.");
+                    errors.err(&field.field, "Later positional argument declared here.");
+                    return;
+                }
+                if let (Some(greedy), Some(_)) = (&field.attrs.greedy, next) {
+                    errors.err(greedy, "Only the last positional argument may be `greedy`.");
+                    return;
+                }
+                repeating = Some(field);
+            }
+            Optionality::Optional | Optionality::Defaulted(_) => {
+                if let Some(first) = repeating {
+                    errors.err(
+                        &first.field,
+                        "A `Vec` positional argument may only be followed by required ones.",
+                    );
+                    errors.err(&field.field, "Later positional argument declared here.");
+                    return;
+                }
+                if let Some(next) = next {
+                    errors.err(
+                        &field.field,
+                        "Only the last positional argument may be `Option` or defaulted.",
+                    );
+                    errors.err(&next.field, "Later positional argument declared here.");
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Returns the repeating positional field if it's followed by other positional
+/// fields. Such a field requires at least one value (e.g. `<source>...` in
+/// `<source>... <dest>`).
+fn non_final_repeating_positional<'a>(
+    fields: &'a [StructField<'a>],
+) -> Option<&'a StructField<'a>> {
+    let mut positionals = fields
+        .iter()
+        .filter(|field| field.kind == FieldKind::Positional);
+    let repeating = positionals
+        .by_ref()
+        .find(|field| field.optionality == Optionality::Repeating)?;
+    positionals.next().map(|_| repeating)
+}
+
+/// Ensures that `conflicts_with` and `group` attributes refer to existing
+/// options or switches and groups, and that groups contain only optional
+/// fields.
+fn ensure_valid_conflicts_and_groups(
+    errors: &Errors,
+    type_attrs: &TypeAttrs,
+    fields: &[StructField<'_>],
+) {
     for field in fields {
-        if field.kind == FieldKind::Positional {
-            if let Some(first) = first_non_required_span {
-                errors.err_span(
-                    first,
-                    "Only the last positional argument may be `Option`, `Vec`, or defaulted.",
+        for other in &field.attrs.conflicts_with {
+            if *field.name == other.value() {
+                errors.err(other, "An option cannot conflict with itself.");
+            } else {
+                referred_field(errors, fields, &other.value(), other, "conflicts_with");
+            }
+        }
+
+        if let Some(group) = &field.attrs.group {
+            if !type_attrs
+                .groups
+                .iter()
+                .any(|g| g.name.value() == group.value())
+            {
+                errors.err(
+                    group,
+                    &format!(
+                        "Unknown group \"{0}\"; declare it on the struct using \
+                         `#[argp(group(name = \"{0}\"))]`.",
+                        group.value()
+                    ),
                 );
-                errors.err(&field.field, "Later positional argument declared here.");
-                return;
+            } else if field.optionality.is_required() {
+                errors
+                    .err(&field.field, "Fields in a group must be `Option`, `Vec`, or defaulted.");
+            }
+        }
+    }
+
+    for group in &type_attrs.groups {
+        if group_members(fields, group).next().is_none() {
+            errors.err(&group.name, "The group has no members.");
+        }
+    }
+}
+
+/// Ensures that `requires`, `required_if` and `required_unless` attributes
+/// refer to existing options or switches, and that the conditionally required
+/// fields are optional.
+fn ensure_valid_requirements(errors: &Errors, fields: &[StructField<'_>]) {
+    for field in fields {
+        let refs = field
+            .attrs
+            .requires
+            .iter()
+            .map(|lit| (lit, "requires"))
+            .chain(
+                field
+                    .attrs
+                    .required_unless
+                    .iter()
+                    .map(|lit| (lit, "required_unless")),
+            );
+        for (other, attr) in refs {
+            if *field.name == other.value() {
+                errors.err(other, "An option cannot refer to itself.");
+            } else {
+                referred_field(errors, fields, &other.value(), other, attr);
+            }
+        }
+
+        for cond in &field.attrs.required_if {
+            let (name, value) = split_required_if(cond);
+            if *field.name == name {
+                errors.err(cond, "An option cannot refer to itself.");
+                continue;
             }
-            if !field.optionality.is_required() {
-                first_non_required_span = Some(field.field.span());
+            match referred_field(errors, fields, &name, cond, "required_if") {
+                Some(other)
+                    if value.is_some()
+                        && (other.kind != FieldKind::Option || other.multi_value_tys.is_some()) =>
+                {
+                    errors.err(
+                        cond,
+                        "`required_if` with a value must refer to an `#[argp(option)]` field \
+                         that takes a single value.",
+                    );
+                }
+                // Other possible values are checked in `ensure_valid_required_if_values`.
+                Some(StructField {
+                    attrs:
+                        FieldAttrs {
+                            possible_values: Some(possible_values),
+                            ..
+                        },
+                    ..
+                }) if value
+                    .as_ref()
+                    .is_some_and(|value| possible_values.iter().all(|v| v.value() != *value)) =>
+                {
+                    errors.err(
+                        cond,
+                        "The value of `required_if` must be one of the `possible_values` of \
+                         the option.",
+                    );
+                }
+                _ => {}
             }
         }
+
+        let conditional = field
+            .attrs
+            .required_if
+            .first()
+            .or(field.attrs.required_unless.first());
+        if let (Some(attr), true) = (conditional, field.optionality.is_required()) {
+            errors.err(
+                attr,
+                "Fields with `required_if` or `required_unless` must be `Option`, `Vec`, \
+                 or defaulted.",
+            );
+        }
+    }
+}
+
+/// Ensures that the values of `required_if` attributes are possible values of
+/// the referred options, if their `FromArgValue` implementation specifies them
+/// (e.g. a derived enum). This is checked in constant assertions, so it cannot
+/// be used for generic structs.
+fn ensure_valid_required_if_values(fields: &[StructField<'_>]) -> TokenStream {
+    let assertions = fields.iter().flat_map(|field| {
+        field.attrs.required_if.iter().filter_map(|cond| {
+            let (name, value) = split_required_if(cond);
+            let value = value?;
+            let other = fields.iter().find(|other| {
+                *other.name == name
+                    && other.kind == FieldKind::Option
+                    && other.multi_value_tys.is_none()
+                    && other.attrs.possible_values.is_none()
+            })?;
+            let possible_values = help::possible_values(other);
+            Some(quote_spanned! { cond.span() =>
+                const _: () = ::argp::parser::ensure_possible_value(#possible_values, #value);
+            })
+        })
+    });
+    quote! { #( #assertions )* }
+}
+
+/// Returns the option or switch field of the given name referred to in the
+/// attribute `attr`, or reports an error if there's no such field.
+fn referred_field<'a>(
+    errors: &Errors,
+    fields: &'a [StructField<'a>],
+    name: &str,
+    lit: &syn::LitStr,
+    attr: &str,
+) -> Option<&'a StructField<'a>> {
+    let field = fields.iter().find(|field| {
+        field.name == name && matches!(field.kind, FieldKind::Option | FieldKind::Switch)
+    });
+    if field.is_none() {
+        errors.err(
+            lit,
+            &format!(
+                "`{}` must refer to an `#[argp(option)]` or `#[argp(switch)]` field of this struct.",
+                attr
+            ),
+        );
+    }
+    field
+}
+
+/// Splits the value of the `required_if` attribute into the field name and the
+/// optional value (e.g. `auth=token`).
+fn split_required_if(lit: &syn::LitStr) -> (String, Option<String>) {
+    let cond = lit.value();
+    match cond.split_once('=') {
+        Some((name, value)) => (name.trim().to_owned(), Some(value.trim().to_owned())),
+        None => (cond.trim().to_owned(), None),
     }
 }
 
@@ -439,8 +1063,27 @@ fn ensure_unique_names(errors: &Errors, fields: &[StructField<'_>]) {
     let mut seen_long_names = HashMap::new();
 
     for field in fields {
-        if let Some(short_name) = &field.attrs.short {
-            let short_name = short_name.value();
+        let aliases: Vec<String> = field
+            .attrs
+            .aliases
+            .iter()
+            .chain(&field.attrs.hidden_aliases)
+            .map(syn::LitStr::value)
+            .collect();
+        let short_aliases = aliases
+            .iter()
+            .filter(|alias| !alias.starts_with("--"))
+            .filter_map(|alias| alias.chars().nth(1));
+        let long_aliases = aliases.iter().filter(|alias| alias.starts_with("--"));
+
+        for short_name in field
+            .attrs
+            .short
+            .as_ref()
+            .map(|s| s.value())
+            .into_iter()
+            .chain(short_aliases)
+        {
             if let Some(first_use_field) = seen_short_names.get(&short_name) {
                 errors.err_span_tokens(
                     first_use_field,
@@ -452,8 +1095,13 @@ fn ensure_unique_names(errors: &Errors, fields: &[StructField<'_>]) {
             seen_short_names.insert(short_name, &field.field);
         }
 
-        if let Some(long_name) = &field.long_name {
-            if let Some(first_use_field) = seen_long_names.get(&long_name) {
+        for long_name in field
+            .long_name
+            .iter()
+            .chain(&field.negated_long_name)
+            .chain(long_aliases)
+        {
+            if let Some(first_use_field) = seen_long_names.get(long_name.as_str()) {
                 errors.err_span_tokens(
                     *first_use_field,
                     &format!("The long name of \"{}\" was already used here.", long_name),
@@ -461,7 +1109,7 @@ fn ensure_unique_names(errors: &Errors, fields: &[StructField<'_>]) {
                 errors.err_span_tokens(field.field, "Later usage here.");
             }
 
-            seen_long_names.insert(long_name, field.field);
+            seen_long_names.insert(long_name.clone(), field.field);
         }
     }
 }
@@ -479,17 +1127,99 @@ fn ensure_only_one_subcommand(errors: &Errors, fields: &[StructField<'_>]) {
     }
 }
 
-fn ensure_global_only_when_subcommand(errors: &Errors, fields: &[StructField<'_>]) {
-    if !fields
+/// Returns the trait bounds required by the types of the fields: `SubCommands`
+/// for the subcommand, `Flatten` and `CommandHelp` for the flattened structs,
+/// and `FromArgValue` for the values parsed without a custom function.
+fn struct_field_bounds<'a>(
+    fields: &'a [StructField<'a>],
+) -> impl Iterator<Item = (&'a Type, TokenStream)> + 'a {
+    fields.iter().flat_map(|field| {
+        let bounds: Vec<_> = match field.kind {
+            FieldKind::SubCommand => {
+                vec![(field.ty_without_wrapper, quote! { ::argp::SubCommands })]
+            }
+            FieldKind::Flatten => vec![(
+                field.ty_without_wrapper,
+                quote! { ::argp::parser::Flatten + ::argp::CommandHelp },
+            )],
+            FieldKind::Option | FieldKind::Positional
+                if field.attrs.from_str_fn.is_none() && field.attrs.from_os_str_fn.is_none() =>
+            {
+                match &field.multi_value_tys {
+                    Some(value_tys) => value_tys.clone(),
+                    None => vec![field.ty_without_wrapper],
+                }
+                .into_iter()
+                .map(|ty| (ty, quote! { ::argp::FromArgValue }))
+                .collect()
+            }
+            FieldKind::Option | FieldKind::Positional | FieldKind::Switch => vec![],
+        };
+        bounds
+    })
+}
+
+/// Returns the generics with the `where` clause extended by the given bounds
+/// for the types that refer to any of the type parameters, so they don't have
+/// to be specified on the type (e.g. `S: ::argp::SubCommands` for a generic
+/// subcommand field `cmd: S`).
+fn with_inferred_bounds<'a>(
+    generics: &syn::Generics,
+    bounds: impl IntoIterator<Item = (&'a Type, TokenStream)>,
+) -> syn::Generics {
+    let params: HashSet<_> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+
+    fn refers_to(tokens: TokenStream, params: &HashSet<syn::Ident>) -> bool {
+        tokens.into_iter().any(|tree| match tree {
+            proc_macro2::TokenTree::Ident(ident) => params.contains(&ident),
+            proc_macro2::TokenTree::Group(group) => refers_to(group.stream(), params),
+            _ => false,
+        })
+    }
+
+    let mut generics = generics.clone();
+    if params.is_empty() {
+        return generics;
+    }
+    let where_clause = generics.make_where_clause();
+    for (ty, bound) in bounds {
+        if refers_to(ty.to_token_stream(), &params) {
+            where_clause
+                .predicates
+                .push(syn::parse_quote! { #ty: #bound });
+        }
+    }
+    generics
+}
+
+/// Ensures that `global` is used only in a struct with a subcommand, unless the
+/// struct is meant to be flattened (`#[argp(flatten)]`), and that such a struct
+/// doesn't have a subcommand.
+fn ensure_valid_flatten_only(errors: &Errors, type_attrs: &TypeAttrs, fields: &[StructField<'_>]) {
+    let subcommand = fields
         .iter()
-        .any(|field| field.kind == FieldKind::SubCommand)
-    {
-        for field in fields.iter().filter(|field| field.attrs.global) {
-            errors.err(
-                field.field,
-                "`global` may only be used when the struct contains a `#[argp(subcommand)]` field.",
-            );
+        .find(|field| field.kind == FieldKind::SubCommand);
+
+    match (subcommand, &type_attrs.flatten) {
+        (Some(subcommand), Some(_)) => errors.err(
+            subcommand.field,
+            "A struct with `#[argp(flatten)]` cannot have a `#[argp(subcommand)]` field.",
+        ),
+        (None, None) => {
+            for field in fields.iter().filter(|field| field.attrs.global) {
+                errors.err(
+                    field.field,
+                    concat!(
+                        "`global` may only be used when the struct contains a `#[argp(subcommand)]` ",
+                        "field, or has the `#[argp(flatten)]` attribute.",
+                    ),
+                );
+            }
         }
+        _ => {}
     }
 }
 
@@ -515,12 +1245,16 @@ fn top_or_sub_cmd_impl(
             errors.err(name, "`#[argp(name = \"...\")]` attribute is required for subcommands");
             &empty_str
         });
+        let aliases = &type_attrs.aliases;
+        let hidden_aliases = &type_attrs.hidden_aliases;
         quote! {
             #[automatically_derived]
             impl #impl_generics ::argp::SubCommand for #name #ty_generics #where_clause {
                 const COMMAND: &'static ::argp::CommandInfo = &::argp::CommandInfo {
                     name: #subcommand_name,
                     description: #description,
+                    aliases: &[ #( #aliases, )* ],
+                    hidden_aliases: &[ #( #hidden_aliases, )* ],
                 };
             }
         }
@@ -549,41 +1283,137 @@ fn declare_local_storage_for_from_args_fields<'a>(
 
         match field.kind {
             FieldKind::Option | FieldKind::Positional => {
-                let parse_func = match (&field.attrs.from_os_str_fn, &field.attrs.from_str_fn) {
-                    (Some(from_os_str_fn), _) => quote! {
-                        |_, value| { #from_os_str_fn(value).map_err(|e| e.to_string()) }
-                    },
-                    (_, Some(from_str_fn)) => quote! {
-                        |_, value| {
-                            value.to_str()
-                                 .ok_or("not a valid UTF-8 string".to_owned())
-                                 .and_then(#from_str_fn)
-                                 .map_err(|e| e.to_string())
+                if let Some(value_tys) = &field.multi_value_tys {
+                    let arity = value_tys.len();
+                    let parse_next_values = value_tys.iter().map(|ty| {
+                        let parse_func = value_parse_func(field, ty);
+                        quote! {
+                            ::argp::parser::parse_next_value(__arg, &mut __values, #parse_func)?
                         }
-                    },
-                    (None, None) => quote! {
-                        |_, value| { <#field_type as ::argp::FromArgValue>::from_arg_value(value) }
-                    }
-                };
+                    });
+                    let value = match field.ty_without_wrapper {
+                        syn::Type::Array(_) => quote! { [ #( #parse_next_values, )* ] },
+                        _ => quote! { ( #( #parse_next_values, )* ) },
+                    };
 
-                quote! {
-                    let mut #field_name: ::argp::parser::ParseValueSlotTy<#field_slot_type, #field_type>
-                        = ::argp::parser::ParseValueSlotTy {
-                            slot: ::std::default::Default::default(),
-                            parse_func: #parse_func,
-                        };
+                    return quote! {
+                        let mut #field_name: ::argp::parser::ParseMultiValueSlotTy<#field_slot_type, #field_type>
+                            = ::argp::parser::ParseMultiValueSlotTy {
+                                slot: ::std::default::Default::default(),
+                                parse_func: |__arg, __values| {
+                                    let mut __values = __values.iter().copied();
+                                    ::std::result::Result::Ok(#value)
+                                },
+                                arity: #arity,
+                            };
+                    };
+                }
+
+                let parse_func = value_parse_func(field, field_type);
+
+                if let Some(delimiter) = &field.attrs.delimiter {
+                    quote! {
+                        let mut #field_name: ::argp::parser::ParseDelimitedValueSlotTy<#field_type>
+                            = ::argp::parser::ParseDelimitedValueSlotTy {
+                                slot: ::std::default::Default::default(),
+                                parse_func: #parse_func,
+                                delimiter: #delimiter,
+                            };
+                    }
+                } else {
+                    quote! {
+                        let mut #field_name: ::argp::parser::ParseValueSlotTy<#field_slot_type, #field_type>
+                            = ::argp::parser::ParseValueSlotTy {
+                                slot: ::std::default::Default::default(),
+                                parse_func: #parse_func,
+                            };
+                    }
                 }
             }
-            FieldKind::SubCommand => {
+            FieldKind::SubCommand | FieldKind::Flatten => {
                 quote! { let mut #field_name: #field_slot_type = ::std::option::Option::None; }
             }
-            FieldKind::Switch => {
-                quote! { let mut #field_name: #field_slot_type = ::argp::parser::Flag::default(); }
-            }
+            FieldKind::Switch => match &field.negated_long_name {
+                Some(negated_long_name) => quote! {
+                    let mut #field_name: ::argp::parser::NegatableFlag<#field_slot_type>
+                        = ::argp::parser::NegatableFlag {
+                            slot: ::argp::parser::Flag::default(),
+                            negative: #negated_long_name,
+                        };
+                },
+                None => quote! {
+                    let mut #field_name: #field_slot_type = ::argp::parser::Flag::default();
+                },
+            },
         }
     })
 }
 
+/// Returns a closure that parses a single value of type `ty` for the given
+/// field, using `from_os_str_fn`, `from_str_fn` or `FromArgValue`.
+fn value_parse_func(field: &StructField<'_>, ty: &syn::Type) -> TokenStream {
+    let parse_func = match (&field.attrs.from_os_str_fn, &field.attrs.from_str_fn) {
+        (Some(from_os_str_fn), _) => quote! {
+            |_, value| { #from_os_str_fn(value).map_err(|e| e.to_string()) }
+        },
+        (_, Some(from_str_fn)) => quote! {
+            |_, value| {
+                value.to_str()
+                     .ok_or("not a valid UTF-8 string".to_owned())
+                     .and_then(#from_str_fn)
+                     .map_err(|e| e.to_string())
+            }
+        },
+        (None, None) => quote! {
+            |_, value| { <#ty as ::argp::FromArgValue>::from_arg_value(value) }
+        },
+    };
+
+    let check_possible_values = field.possible_values_const.as_ref().map(|ident| {
+        let values = field
+            .attrs
+            .possible_values
+            .as_ref()
+            .expect("set with the constant");
+        let msg = format!(
+            "must be one of: {}",
+            values
+                .iter()
+                .map(LitStr::value)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        quote! {
+            if !__value.to_str().map_or(false, |v| #ident.contains(&v)) {
+                return ::std::result::Result::Err(#msg.to_owned());
+            }
+        }
+    });
+    let check_range = field.attrs.range.as_ref().map(|range| {
+        let msg = format!("must be in the range {}", range.value());
+        let range = range
+            .parse::<syn::ExprRange>()
+            .expect("validated in FieldAttrs");
+        quote! {
+            if !(#range).contains(&__parsed) {
+                return ::std::result::Result::Err(#msg.to_owned());
+            }
+        }
+    });
+
+    if check_possible_values.is_none() && check_range.is_none() {
+        return parse_func;
+    }
+    quote! {
+        |__arg, __value| {
+            #check_possible_values
+            let __parsed = (#parse_func)(__arg, __value)?;
+            #check_range
+            ::std::result::Result::Ok(__parsed)
+        }
+    }
+}
+
 /// Unwrap non-optional fields and take options out of their tuple slots.
 fn unwrap_from_args_fields<'a>(
     fields: &'a [StructField<'a>],
@@ -604,8 +1434,11 @@ fn unwrap_from_args_fields<'a>(
                     }
                 }
             },
+            FieldKind::Switch if field.negated_long_name.is_some() => {
+                quote! { #field_name: #field_name.slot }
+            }
             FieldKind::Switch => field_name.into_token_stream(),
-            FieldKind::SubCommand => match field.optionality {
+            FieldKind::SubCommand | FieldKind::Flatten => match field.optionality {
                 Optionality::None => quote! { #field_name: #field_name.unwrap() },
                 Optionality::Optional | Optionality::Repeating => field_name.into_token_stream(),
                 Optionality::Defaulted(_) => unreachable!(),
@@ -614,9 +1447,9 @@ fn unwrap_from_args_fields<'a>(
     })
 }
 
-/// Entries of tokens like `("--some-flag-key", 5)` that map from a flag key string
-/// to an index in the output table.
-fn flag_str_to_output_table_map_entries<'a>(fields: &'a [StructField<'a>]) -> Vec<TokenStream> {
+/// Entries like `("--some-flag-key", 5)` that map from a flag key string to an
+/// index in the output table.
+fn flag_str_to_output_table_map_entries(fields: &[StructField<'_>]) -> Vec<(String, usize)> {
     let mut flag_str_to_output_table_map = vec![];
     for (i, (field, long_name)) in fields
         .iter()
@@ -625,32 +1458,56 @@ fn flag_str_to_output_table_map_entries<'a>(fields: &'a [StructField<'a>]) -> Ve
     {
         if let Some(short) = &field.attrs.short {
             let short = format!("-{}", short.value());
-            flag_str_to_output_table_map.push(quote! { (#short, #i) });
+            flag_str_to_output_table_map.push((short, i));
+        }
+
+        flag_str_to_output_table_map.push((long_name.clone(), i));
+
+        if let Some(negated_long_name) = &field.negated_long_name {
+            flag_str_to_output_table_map.push((negated_long_name.clone(), i));
         }
 
-        flag_str_to_output_table_map.push(quote! { (#long_name, #i) });
+        for alias in field
+            .attrs
+            .aliases
+            .iter()
+            .chain(&field.attrs.hidden_aliases)
+        {
+            flag_str_to_output_table_map.push((alias.value(), i));
+        }
     }
     flag_str_to_output_table_map
 }
 
-/// For each non-optional field, add an entry to the `::argp::MissingRequirements`.
+/// For each non-optional field (and a repeating positional field followed by
+/// other ones), add an entry to the `::argp::MissingRequirements`.
 fn append_missing_requirements<'a>(
     // missing_requirements_ident
     mri: &syn::Ident,
     fields: &'a [StructField<'a>],
 ) -> impl Iterator<Item = TokenStream> + 'a {
     let mri = mri.clone();
+    let non_final_repeating = non_final_repeating_positional(fields).map(|f| f.name);
     fields
         .iter()
-        .filter(|f| f.optionality.is_required())
+        .filter(move |f| {
+            (f.optionality.is_required() && f.kind != FieldKind::Flatten)
+                || Some(f.name) == non_final_repeating
+        })
         .map(move |field| {
             let field_name = field.name;
             match field.kind {
                 FieldKind::Switch => unreachable!("switches are always optional"),
+                FieldKind::Flatten => unreachable!("flattened fields are filtered out"),
                 FieldKind::Positional => {
                     let name = field.positional_arg_name();
+                    let is_missing = if field.optionality == Optionality::Repeating {
+                        quote! { #field_name.slot.is_empty() }
+                    } else {
+                        quote! { #field_name.slot.is_none() }
+                    };
                     quote! {
-                        if #field_name.slot.is_none() {
+                        if #is_missing {
                             #mri.missing_positional_arg(#name);
                         }
                     }
@@ -687,6 +1544,256 @@ fn append_missing_requirements<'a>(
         })
 }
 
+/// Returns the position of the given option or switch in the `slots` table,
+/// i.e. its index among the option and switch fields.
+fn slot_position(fields: &[StructField<'_>], name: &str) -> Option<usize> {
+    fields
+        .iter()
+        .filter(|field| matches!(field.kind, FieldKind::Option | FieldKind::Switch))
+        .position(|field| field.name == name)
+}
+
+/// Returns the option and switch fields that are members of the given group.
+fn group_members<'a>(
+    fields: &'a [StructField<'a>],
+    group: &'a GroupAttrs,
+) -> impl Iterator<Item = &'a StructField<'a>> {
+    fields.iter().filter(move |field| {
+        field
+            .attrs
+            .group
+            .as_ref()
+            .is_some_and(|name| name.value() == group.name.value())
+    })
+}
+
+/// Returns an optional table with an entry for each option and switch in
+/// `fields`, or `None` if `entry` returns `None` for all of them. A missing
+/// entry is filled with the default value of the table's element type.
+fn optional_flag_table(
+    fields: &[StructField<'_>],
+    entry: impl Fn(&StructField<'_>) -> Option<TokenStream>,
+) -> TokenStream {
+    let entries: Vec<_> = fields
+        .iter()
+        .filter(|field| matches!(field.kind, FieldKind::Option | FieldKind::Switch))
+        .map(entry)
+        .collect();
+
+    if entries.iter().all(Option::is_none) {
+        return quote! { ::std::option::Option::None };
+    }
+    let entries = entries
+        .into_iter()
+        .map(|entry| entry.unwrap_or_else(|| quote! { ::std::default::Default::default() }));
+    quote! { ::std::option::Option::Some(&[ #( #entries, )* ]) }
+}
+
+/// Returns pairs of positions in the options slots of the options that cannot
+/// be used together, either due to `conflicts_with` or a non-multiple group.
+fn conflicting_slots(type_attrs: &TypeAttrs, fields: &[StructField<'_>]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+
+    for field in fields {
+        for other in &field.attrs.conflicts_with {
+            pairs.push((field.name.to_string(), other.value()));
+        }
+    }
+    for group in type_attrs.groups.iter().filter(|group| !group.multiple) {
+        let members: Vec<_> = group_members(fields, group).collect();
+        for (i, field) in members.iter().enumerate() {
+            for other in &members[i + 1..] {
+                pairs.push((field.name.to_string(), other.name.to_string()));
+            }
+        }
+    }
+
+    let mut seen = HashSet::new();
+    pairs
+        .into_iter()
+        .filter_map(|(a, b)| {
+            let a = slot_position(fields, &a)?;
+            let b = slot_position(fields, &b)?;
+            Some((a.min(b), a.max(b)))
+        })
+        .filter(|pair| seen.insert(*pair))
+        .collect()
+}
+
+/// For each pair of options that cannot be used together (`conflicts_with` or
+/// members of a group that doesn't allow `multiple`), returns an error if both
+/// have been provided.
+fn check_conflicts<'a>(
+    // slots_provided_ident
+    spi: &syn::Ident,
+    conflicting_slots: &[(usize, usize)],
+    fields: &'a [StructField<'a>],
+) -> Vec<TokenStream> {
+    conflicting_slots
+        .iter()
+        .map(|&(a, b)| {
+            let slot_long_name = |pos| {
+                fields
+                    .iter()
+                    .filter(|field| matches!(field.kind, FieldKind::Option | FieldKind::Switch))
+                    .nth(pos)
+                    .and_then(|field| field.long_name.as_ref())
+                    .expect("options always have a long name")
+            };
+            let (a_name, b_name) = (slot_long_name(a), slot_long_name(b));
+            quote! {
+                if #spi[#a] && #spi[#b] {
+                    return ::std::result::Result::Err(::argp::Error::ConflictingOptions {
+                        arg: #a_name.to_owned(),
+                        other: #b_name.to_owned(),
+                    }.into());
+                }
+            }
+        })
+        .collect()
+}
+
+/// For each required group, add an entry to the `::argp::MissingRequirements`
+/// if none of its members has been provided.
+fn append_missing_groups<'a>(
+    // missing_requirements_ident
+    mri: &syn::Ident,
+    // slots_provided_ident
+    spi: &syn::Ident,
+    type_attrs: &'a TypeAttrs,
+    fields: &'a [StructField<'a>],
+) -> Vec<TokenStream> {
+    type_attrs
+        .groups
+        .iter()
+        .filter(|group| group.required && group_members(fields, group).next().is_some())
+        .map(|group| {
+            let members: Vec<_> = group_members(fields, group).collect();
+            let positions = members
+                .iter()
+                .filter_map(|field| slot_position(fields, &field.name.to_string()));
+            let names = members.iter().filter_map(|field| field.long_name.as_ref());
+            quote! {
+                if #( !#spi[#positions] )&&* {
+                    #mri.missing_group(&[ #( #names, )* ]);
+                }
+            }
+        })
+        .collect()
+}
+
+/// For each option that is required because of `requires`, `required_if` or
+/// `required_unless`, add an entry to the `::argp::MissingRequirements` if it
+/// hasn't been provided.
+fn append_conditional_requirements<'a>(
+    // missing_requirements_ident
+    mri: &syn::Ident,
+    // slots_provided_ident
+    spi: &syn::Ident,
+    fields: &'a [StructField<'a>],
+) -> Vec<TokenStream> {
+    let slot = |name: &str| {
+        let pos = slot_position(fields, name)?;
+        let field = fields.iter().find(|field| field.name == name)?;
+        Some((pos, field, field.long_name.as_ref()?))
+    };
+    let mut out = Vec::new();
+
+    for field in fields {
+        let (pos, _, long_name) = match slot(&field.name.to_string()) {
+            Some(slot) => slot,
+            None => continue,
+        };
+
+        for other in &field.attrs.requires {
+            if let Some((other_pos, _, other_long_name)) = slot(&other.value()) {
+                let condition = format!("required by {}", long_name);
+                out.push(quote! {
+                    if #spi[#pos] && !#spi[#other_pos] {
+                        #mri.missing_option_if(#other_long_name, #condition);
+                    }
+                });
+            }
+        }
+
+        for cond in &field.attrs.required_if {
+            let (other_name, value) = split_required_if(cond);
+            let (other_pos, other, other_long_name) = match slot(&other_name) {
+                Some(slot) => slot,
+                None => continue,
+            };
+            let other_ident = &other.field.ident;
+            let (condition, test) = match value {
+                // Reported in `ensure_valid_requirements`.
+                Some(_) if other.kind != FieldKind::Option || other.multi_value_tys.is_some() => {
+                    continue
+                }
+                // The value is parsed only if the other option is provided.
+                Some(value) => (
+                    format!("required if {} is '{}'", other_long_name, value),
+                    quote! {
+                        #spi[#other_pos]
+                            && (#other_ident.parse_func)(#other_long_name, ::std::ffi::OsStr::new(#value))
+                                .is_ok_and(|__expected| #other_ident.slot.iter().any(|__value| *__value == __expected))
+                    },
+                ),
+                None => (
+                    format!("required if {} is provided", other_long_name),
+                    quote! { #spi[#other_pos] },
+                ),
+            };
+            out.push(quote! {
+                if !#spi[#pos] && #test {
+                    #mri.missing_option_if(#long_name, #condition);
+                }
+            });
+        }
+
+        let unless: Vec<_> = field
+            .attrs
+            .required_unless
+            .iter()
+            .filter_map(|other| slot(&other.value()))
+            .collect();
+        if !unless.is_empty() {
+            let names: Vec<_> = unless.iter().map(|(_, _, name)| name.as_str()).collect();
+            let condition = format!("required unless {} is provided", names.join(" or "));
+            let other_pos = unless.iter().map(|(pos, _, _)| pos);
+            out.push(quote! {
+                if !#spi[#pos] #( && !#spi[#other_pos] )* {
+                    #mri.missing_option_if(#long_name, #condition);
+                }
+            });
+        }
+    }
+    out
+}
+
+/// Returns the element types if `ty` is a tuple or an array, i.e. a type of an
+/// option that takes a fixed number of values per occurrence.
+fn ty_multi_value_elems<'a>(errors: &Errors, ty: &'a syn::Type) -> Option<Vec<&'a syn::Type>> {
+    match ty {
+        syn::Type::Tuple(tuple) if !tuple.elems.is_empty() => Some(tuple.elems.iter().collect()),
+        syn::Type::Array(array) => {
+            let len = match &array.len {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Int(len),
+                    ..
+                }) => len.base10_parse::<usize>().ok().filter(|n| *n > 0),
+                _ => None,
+            };
+            match len {
+                Some(len) => Some(vec![&*array.elem; len]),
+                None => {
+                    errors.err(&array.len, "Expected a positive integer literal");
+                    None
+                }
+            }
+        }
+        _ => None,
+    }
+}
+
 /// Require that a type can be a `switch`.
 /// Throws an error for all types except booleans and integers
 fn ty_expect_switch(errors: &Errors, ty: &syn::Type) -> bool {
@@ -754,47 +1861,89 @@ fn ty_inner<'a>(wrapper_names: &[&str], ty: &'a syn::Type) -> Option<&'a syn::Ty
 /// Implements `FromArgs` and `SubCommands` for a `#![derive(FromArgs)]` enum.
 fn impl_from_args_enum(
     errors: &Errors,
-    name: &syn::Ident,
+    enum_name: &syn::Ident,
     type_attrs: &TypeAttrs,
     generic_args: &syn::Generics,
     de: &syn::DataEnum,
 ) -> TokenStream {
     parse_attrs::check_enum_type_attrs(errors, type_attrs, &de.enum_token.span);
 
-    // An enum variant like `<name>(<ty>)`
+    // An enum variant like `<name>(<ty>)`, or an inline subcommand like
+    // `<name> { <fields> }` or `<name>` with `ty` referring to the generated
+    // struct
     struct SubCommandVariant<'a> {
         name: &'a syn::Ident,
-        ty: &'a syn::Type,
+        ty: Type,
+        fields: Option<Vec<&'a syn::Ident>>,
     }
 
     let mut dynamic_type_and_variant = None;
+    let mut inline_structs = Vec::new();
 
     let variants: Vec<SubCommandVariant<'_>> = de
         .variants
         .iter()
         .filter_map(|variant| {
             let name = &variant.ident;
-            let ty = enum_only_single_field_unnamed_variants(errors, &variant.fields)?;
-            if parse_attrs::VariantAttrs::parse(errors, variant)
-                .is_dynamic
-                .is_some()
-            {
+            let variant_attrs = parse_attrs::VariantAttrs::parse(errors, variant);
+
+            let ty = match enum_variant_kind(errors, &variant.fields)? {
+                VariantKind::SingleField(ty) => ty,
+                VariantKind::Inline(fields) => {
+                    if !generic_args.params.is_empty() {
+                        errors.err(
+                            variant,
+                            "Inline subcommands are not supported in generic `enum`s.",
+                        );
+                        return None;
+                    }
+                    let (ty, item) = inline_subcommand_struct(enum_name, variant, &variant_attrs);
+                    inline_structs.push(item);
+                    return Some(SubCommandVariant {
+                        name,
+                        ty,
+                        fields: Some(fields.iter().filter_map(|f| f.ident.as_ref()).collect()),
+                    });
+                }
+            };
+
+            if variant_attrs.is_dynamic.is_some() {
                 if dynamic_type_and_variant.is_some() {
                     errors.err(variant, "Only one variant can have the `dynamic` attribute");
                 }
                 dynamic_type_and_variant = Some((ty, name));
                 None
             } else {
-                Some(SubCommandVariant { name, ty })
+                Some(SubCommandVariant {
+                    name,
+                    ty: ty.clone(),
+                    fields: None,
+                })
             }
         })
         .collect();
 
-    let variant_ty = variants.iter().map(|x| x.ty).collect::<Vec<_>>();
-    let variant_names = variants.iter().map(|x| x.name).collect::<Vec<_>>();
+    let variant_ty = variants.iter().map(|x| &x.ty).collect::<Vec<_>>();
+    let variant_constructors = variants
+        .iter()
+        .map(|variant| {
+            let name = variant.name;
+            let ty = &variant.ty;
+            match &variant.fields {
+                Some(fields) => quote! {
+                    |#ty { #( #fields, )* }| #enum_name::#name { #( #fields, )* }
+                },
+                None => quote! { #enum_name::#name },
+            }
+        })
+        .collect::<Vec<_>>();
 
-    let from_args_method =
-        impl_from_args_enum_from_args(name, &variant_names, &variant_ty, dynamic_type_and_variant);
+    let from_args_method = impl_from_args_enum_from_args(
+        enum_name,
+        &variant_constructors,
+        &variant_ty,
+        dynamic_type_and_variant,
+    );
 
     let dynamic_commands = dynamic_type_and_variant.as_ref().map(|(dynamic_type, _)| {
         quote! {
@@ -804,16 +1953,28 @@ fn impl_from_args_enum(
         }
     });
 
+    let bounds = variants
+        .iter()
+        .map(|variant| (&variant.ty, quote! { ::argp::SubCommand }))
+        .chain(
+            dynamic_type_and_variant
+                .map(|(dynamic_type, _)| (dynamic_type, quote! { ::argp::DynamicSubCommand })),
+        );
+    let generic_args = &with_inferred_bounds(generic_args, bounds);
+
     let (impl_generics, ty_generics, where_clause) = generic_args.split_for_impl();
     quote! {
-        impl #impl_generics ::argp::FromArgs for #name #ty_generics #where_clause {
+        #( #inline_structs )*
+
+        impl #impl_generics ::argp::FromArgs for #enum_name #ty_generics #where_clause {
             #from_args_method
         }
 
-        impl #impl_generics ::argp::SubCommands for #name #ty_generics #where_clause {
-            const COMMANDS: &'static [&'static ::argp::CommandInfo] = &[#(
-                <#variant_ty as ::argp::SubCommand>::COMMAND,
-            )*];
+        impl #impl_generics ::argp::SubCommands for #enum_name #ty_generics #where_clause {
+            const COMMANDS: &'static [&'static ::argp::CommandInfo] =
+                ::argp::parser::ensure_unique_commands(&[#(
+                    <#variant_ty as ::argp::SubCommand>::COMMAND,
+                )*]);
 
             #dynamic_commands
         }
@@ -822,12 +1983,10 @@ fn impl_from_args_enum(
 
 fn impl_from_args_enum_from_args(
     name: &syn::Ident,
-    variant_names: &[&syn::Ident],
+    variant_constructors: &[TokenStream],
     variant_ty: &[&Type],
     dynamic_type_and_variant: Option<(&Type, &syn::Ident)>,
 ) -> TokenStream {
-    let name_repeating = iter::repeat(name.clone());
-
     let dynamic_from_args =
         dynamic_type_and_variant.as_ref().map(|(dynamic_type, dynamic_variant)| {
             quote! {
@@ -853,10 +2012,9 @@ fn impl_from_args_enum_from_args(
             };
 
             #(
-                if subcommand_name == <#variant_ty as ::argp::SubCommand>::COMMAND.name {
-                    return ::std::result::Result::Ok(#name_repeating::#variant_names(
-                        <#variant_ty as ::argp::FromArgs>::_from_args(command_name, args, parent)?
-                    ));
+                if <#variant_ty as ::argp::SubCommand>::COMMAND.matches(subcommand_name) {
+                    return <#variant_ty as ::argp::FromArgs>::_from_args(command_name, args, parent)
+                        .map(#variant_constructors);
                 }
             )*
 
@@ -869,62 +2027,89 @@ fn impl_from_args_enum_from_args(
     }
 }
 
-/// Returns `Some(Bar)` if the field is a single-field unnamed variant like `Foo(Bar)`.
-/// Otherwise, generates an error.
-fn enum_only_single_field_unnamed_variants<'a>(
+/// The kind of a `#![derive(FromArgs)]` enum variant.
+enum VariantKind<'a> {
+    /// A single-field unnamed variant like `Foo(Bar)`.
+    SingleField(&'a syn::Type),
+    /// A variant with named fields like `Foo { bar: Bar }`, or a unit variant
+    /// like `Foo` (with no fields).
+    Inline(Vec<&'a syn::Field>),
+}
+
+/// Returns the kind of the variant, or generates an error if it's an unnamed
+/// variant with other than one field.
+fn enum_variant_kind<'a>(
     errors: &Errors,
     variant_fields: &'a syn::Fields,
-) -> Option<&'a syn::Type> {
-    macro_rules! with_enum_suggestion {
-        ($help_text:literal) => {
-            concat!(
-                $help_text,
-                "\nInstead, use a variant with a single unnamed field for each subcommand:\n",
-                "    enum MyCommandEnum {\n",
-                "        SubCommandOne(SubCommandOne),\n",
-                "        SubCommandTwo(SubCommandTwo),\n",
-                "    }",
-            )
-        };
-    }
-
+) -> Option<VariantKind<'a>> {
     match variant_fields {
-        syn::Fields::Named(fields) => {
-            errors.err(
-                fields,
-                with_enum_suggestion!(
-                    "`#![derive(FromArgs)]` `enum`s do not support variants with named fields."
-                ),
-            );
-            None
-        }
-        syn::Fields::Unit => {
-            errors.err(
-                variant_fields,
-                with_enum_suggestion!(
-                    "`#![derive(FromArgs)]` does not support `enum`s with no variants."
-                ),
-            );
-            None
-        }
+        syn::Fields::Named(fields) => Some(VariantKind::Inline(fields.named.iter().collect())),
+        syn::Fields::Unit => Some(VariantKind::Inline(Vec::new())),
         syn::Fields::Unnamed(fields) => {
             if fields.unnamed.len() != 1 {
                 errors.err(
                     fields,
-                    with_enum_suggestion!(
-                        "`#![derive(FromArgs)]` `enum` variants must only contain one field."
+                    concat!(
+                        "`#![derive(FromArgs)]` `enum` variants must only contain one field.\n",
+                        "Instead, use a variant with a single unnamed field for each subcommand,\n",
+                        "or a variant with named fields:\n",
+                        "    enum MyCommandEnum {\n",
+                        "        SubCommandOne(SubCommandOne),\n",
+                        "        SubCommandTwo { #[argp(positional)] name: String },\n",
+                        "    }",
                     ),
                 );
                 None
             } else {
                 // `unwrap` is okay because of the length check above.
                 let first_field = fields.unnamed.first().unwrap();
-                Some(&first_field.ty)
+                Some(VariantKind::SingleField(&first_field.ty))
             }
         }
     }
 }
 
+/// Generates a struct deriving `FromArgs` for an inline subcommand (a variant
+/// with named fields or a unit variant), with the fields and attributes of the
+/// variant. The name of the subcommand defaults to the kebab-case'd variant
+/// name. Returns the type of the struct and the struct item.
+///
+/// The struct is named `__{Enum}__{Variant}`, the separator avoids collisions
+/// between e.g. `A::BC` and `AB::C`.
+fn inline_subcommand_struct(
+    enum_name: &syn::Ident,
+    variant: &syn::Variant,
+    variant_attrs: &parse_attrs::VariantAttrs,
+) -> (Type, TokenStream) {
+    let ident =
+        syn::Ident::new(&format!("__{}__{}", enum_name, variant.ident), variant.ident.span());
+    // Other attributes (e.g. `#[default]`) belong to the enum's derives.
+    let attrs = variant
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc") || attr.path().is_ident("argp"));
+    let fields = variant.fields.iter();
+
+    let name_attr = if variant_attrs.name.is_none() {
+        let name = from_arg_value::to_kebab_case(&variant.ident.to_string());
+        Some(quote! { #[argp(name = #name)] })
+    } else {
+        None
+    };
+
+    let item = quote! {
+        #[allow(non_camel_case_types)]
+        #[derive(::argp::FromArgs)]
+        #[argp(subcommand)]
+        #name_attr
+        #( #attrs )*
+        struct #ident {
+            #( #fields, )*
+        }
+    };
+    (syn::parse_quote! { #ident }, item)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -21,8 +21,23 @@ pub struct FieldAttrs {
     pub short: Option<syn::LitChar>,
     pub arg_name: Option<syn::LitStr>,
     pub greedy: Option<syn::Path>,
+    pub allow_hyphen_values: Option<syn::Path>,
     pub hidden_help: bool,
     pub global: bool,
+    pub negatable: Option<syn::Path>,
+    pub overridable: Option<syn::Path>,
+    pub aliases: Vec<syn::LitStr>,
+    pub hidden_aliases: Vec<syn::LitStr>,
+    pub env: Option<syn::LitStr>,
+    pub delimiter: Option<syn::LitChar>,
+    pub default_missing: Option<syn::LitStr>,
+    pub conflicts_with: Vec<syn::LitStr>,
+    pub group: Option<syn::LitStr>,
+    pub requires: Vec<syn::LitStr>,
+    pub required_if: Vec<syn::LitStr>,
+    pub required_unless: Vec<syn::LitStr>,
+    pub range: Option<syn::LitStr>,
+    pub possible_values: Option<Vec<syn::LitStr>>,
 }
 
 /// The purpose of a particular field on a `#![derive(FromArgs)]` struct.
@@ -42,6 +57,9 @@ pub enum FieldKind {
     /// They are parsed in declaration order, and only the last positional
     /// argument in a type may be an `Option`, `Vec`, or have a default value.
     Positional,
+    /// Flattened fields refer to structs implementing `FromArgs` whose options
+    /// and positional arguments are embedded into the containing struct.
+    Flatten,
 }
 
 /// The type of a field on a `#![derive(FromArgs)]` struct.
@@ -82,18 +100,56 @@ impl FieldAttrs {
 
             for meta in ml {
                 let name = meta.path();
-                if name.is_ident("arg_name") {
+                if name.is_ident("alias") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_multi_string(errors, m, &mut this.aliases);
+                    }
+                } else if name.is_ident("aliases") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_string_array(errors, m, &mut this.aliases);
+                    }
+                } else if name.is_ident("allow_hyphen_values") {
+                    parse_attr_word(
+                        errors,
+                        &meta,
+                        "allow_hyphen_values",
+                        &mut this.allow_hyphen_values,
+                    );
+                } else if name.is_ident("arg_name") {
                     if let Some(m) = errors.expect_meta_name_value(&meta) {
                         parse_attr_single_string(errors, m, "arg_name", &mut this.arg_name);
                     }
+                } else if name.is_ident("conflicts_with") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_multi_string(errors, m, &mut this.conflicts_with);
+                    }
                 } else if name.is_ident("default") {
                     if let Some(m) = errors.expect_meta_name_value(&meta) {
                         parse_attr_single_string(errors, m, "default", &mut this.default);
                     }
+                } else if name.is_ident("default_missing") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_single_string(
+                            errors,
+                            m,
+                            "default_missing",
+                            &mut this.default_missing,
+                        );
+                    }
+                } else if name.is_ident("delimiter") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        this.parse_attr_delimiter(errors, m);
+                    }
                 } else if name.is_ident("description") {
                     if let Some(m) = errors.expect_meta_name_value(&meta) {
                         parse_attr_description(errors, m, &mut this.description);
                     }
+                } else if name.is_ident("env") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_single_string(errors, m, "env", &mut this.env);
+                    }
+                } else if name.is_ident("flatten") {
+                    parse_attr_field_type(errors, &meta, FieldKind::Flatten, &mut this.field_type);
                 } else if name.is_ident("from_str_fn") {
                     if let Some(m) = errors.expect_meta_list(&meta) {
                         parse_attr_fn_path(errors, m, "from_str_fn", &mut this.from_str_fn);
@@ -108,6 +164,28 @@ impl FieldAttrs {
                     }
                 } else if name.is_ident("option") {
                     parse_attr_field_type(errors, &meta, FieldKind::Option, &mut this.field_type);
+                } else if name.is_ident("overridable") {
+                    parse_attr_word(errors, &meta, "overridable", &mut this.overridable);
+                } else if name.is_ident("possible_values") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        this.parse_attr_possible_values(errors, m);
+                    }
+                } else if name.is_ident("range") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        this.parse_attr_range(errors, m);
+                    }
+                } else if name.is_ident("required_if") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_multi_string(errors, m, &mut this.required_if);
+                    }
+                } else if name.is_ident("required_unless") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_multi_string(errors, m, &mut this.required_unless);
+                    }
+                } else if name.is_ident("requires") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_multi_string(errors, m, &mut this.requires);
+                    }
                 } else if name.is_ident("short") {
                     if let Some(m) = errors.expect_meta_name_value(&meta) {
                         this.parse_attr_short(errors, m);
@@ -128,19 +206,33 @@ impl FieldAttrs {
                         FieldKind::Positional,
                         &mut this.field_type,
                     );
+                } else if name.is_ident("group") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_single_string(errors, m, "group", &mut this.group);
+                    }
                 } else if name.is_ident("greedy") {
                     this.greedy = Some(name.clone());
+                } else if name.is_ident("hidden_alias") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_multi_string(errors, m, &mut this.hidden_aliases);
+                    }
                 } else if name.is_ident("hidden_help") {
                     this.hidden_help = true;
                 } else if name.is_ident("global") {
                     this.global = true;
+                } else if name.is_ident("negatable") {
+                    this.negatable = Some(name.clone());
                 } else {
                     errors.err(
                         &meta,
                         concat!(
                             "Invalid field-level `argp` attribute\n",
-                            "Expected one of: `arg_name`, `default`, `description`, `from_os_str_fn`, ",
-                            "`from_str_fn`, `global`, `greedy`, `long`, `option`, `short`, `subcommand`, ",
+                            "Expected one of: `alias`, `aliases`, `allow_hyphen_values`, `arg_name`, ",
+                            "`conflicts_with`, `default`, ",
+                            "`default_missing`, `delimiter`, `description`, `env`, `flatten`, `from_os_str_fn`, ",
+                            "`from_str_fn`, `global`, `greedy`, `group`, `hidden_alias`, `long`, ",
+                            "`negatable`, `option`, `overridable`, `possible_values`, `range`, ",
+                            "`required_if`, `required_unless`, `requires`, `short`, `subcommand`, ",
                             "`switch`, `hidden_help`",
                         ),
                     );
@@ -151,7 +243,7 @@ impl FieldAttrs {
         if let (Some(default), Some(field_type)) = (&this.default, &this.field_type) {
             match field_type.kind {
                 FieldKind::Option | FieldKind::Positional => {}
-                FieldKind::SubCommand | FieldKind::Switch => errors.err(
+                FieldKind::SubCommand | FieldKind::Switch | FieldKind::Flatten => errors.err(
                     default,
                     "`default` may only be specified on `#[argp(option)]` \
                      or `#[argp(positional)]` fields",
@@ -169,10 +261,141 @@ impl FieldAttrs {
             _ => {}
         }
 
+        match (&this.allow_hyphen_values, this.field_type.as_ref().map(|f| f.kind)) {
+            (Some(_), Some(FieldKind::Positional)) => {}
+            (Some(allow_hyphen_values), Some(_)) => errors.err(
+                &allow_hyphen_values,
+                "`allow_hyphen_values` may only be specified on `#[argp(positional)]` fields, \
+                 option values are always taken as is",
+            ),
+            _ => {}
+        }
+
+        match (&this.negatable, this.field_type.as_ref().map(|f| f.kind)) {
+            (Some(_), Some(FieldKind::Switch)) => {}
+            (Some(negatable), Some(_)) => errors
+                .err(&negatable, "`negatable` may only be specified on `#[argp(switch)]` fields"),
+            _ => {}
+        }
+
+        for alias in this.aliases.iter().chain(&this.hidden_aliases) {
+            match this.field_type.as_ref().map(|f| f.kind) {
+                Some(FieldKind::Option | FieldKind::Switch) => check_option_alias(errors, alias),
+                Some(_) => errors.err(
+                    alias,
+                    "Aliases may only be specified on `#[argp(option)]` \
+                        or `#[argp(switch)]` fields",
+                ),
+                None => {}
+            }
+        }
+
+        match (&this.overridable, this.field_type.as_ref().map(|f| f.kind)) {
+            (Some(_), Some(FieldKind::Option | FieldKind::Switch)) => {}
+            (Some(overridable), Some(_)) => errors.err(
+                overridable,
+                "`overridable` may only be specified on `#[argp(option)]` \
+                    or `#[argp(switch)]` fields",
+            ),
+            _ => {}
+        }
+
+        match (&this.default_missing, this.field_type.as_ref().map(|f| f.kind)) {
+            (Some(_), Some(FieldKind::Option)) => {}
+            (Some(default_missing), Some(_)) => errors.err(
+                default_missing,
+                "`default_missing` may only be specified on `#[argp(option)]` fields",
+            ),
+            _ => {}
+        }
+
+        match (&this.delimiter, this.field_type.as_ref().map(|f| f.kind)) {
+            (Some(_), Some(FieldKind::Option)) => {}
+            (Some(delimiter), Some(_)) => errors
+                .err(delimiter, "`delimiter` may only be specified on `#[argp(option)]` fields"),
+            _ => {}
+        }
+
+        if let Some(field_type) = &this.field_type {
+            match field_type.kind {
+                FieldKind::Option | FieldKind::Positional => {}
+                FieldKind::Switch | FieldKind::SubCommand | FieldKind::Flatten => {
+                    if let Some(range) = &this.range {
+                        errors.err(
+                            range,
+                            "`range` may only be specified on `#[argp(option)]` \
+                             or `#[argp(positional)]` fields",
+                        );
+                    }
+                    if let Some(first) = this.possible_values.iter().flatten().next() {
+                        errors.err(
+                            first,
+                            "`possible_values` may only be specified on `#[argp(option)]` \
+                             or `#[argp(positional)]` fields",
+                        );
+                    }
+                }
+            }
+        }
+
+        if let (Some(field_type), Some(env)) = (&this.field_type, &this.env) {
+            match field_type.kind {
+                FieldKind::Option | FieldKind::Switch => {}
+                FieldKind::Positional | FieldKind::SubCommand | FieldKind::Flatten => errors.err(
+                    env,
+                    "`env` may only be specified on `#[argp(option)]` \
+                     or `#[argp(switch)]` fields",
+                ),
+            }
+        }
+
+        if let Some(field_type) = &this.field_type {
+            match field_type.kind {
+                FieldKind::Option | FieldKind::Switch => {}
+                FieldKind::Positional | FieldKind::SubCommand | FieldKind::Flatten => {
+                    if let Some(conflicts_with) = this.conflicts_with.first() {
+                        errors.err(
+                            conflicts_with,
+                            "`conflicts_with` may only be specified on `#[argp(option)]` \
+                             or `#[argp(switch)]` fields",
+                        );
+                    }
+                    if let Some(group) = &this.group {
+                        errors.err(
+                            group,
+                            "`group` may only be specified on `#[argp(option)]` \
+                             or `#[argp(switch)]` fields",
+                        );
+                    }
+                    if let Some(requires) = this.requires.first() {
+                        errors.err(
+                            requires,
+                            "`requires` may only be specified on `#[argp(option)]` \
+                             or `#[argp(switch)]` fields",
+                        );
+                    }
+                }
+            }
+            if field_type.kind != FieldKind::Option {
+                if let Some(required_if) = this.required_if.first() {
+                    errors.err(
+                        required_if,
+                        "`required_if` may only be specified on `#[argp(option)]` fields",
+                    );
+                }
+                if let Some(required_unless) = this.required_unless.first() {
+                    errors.err(
+                        required_unless,
+                        "`required_unless` may only be specified on `#[argp(option)]` fields",
+                    );
+                }
+            }
+        }
+
         if let (Some(field_type), true) = (&this.field_type, this.global) {
             match field_type.kind {
                 FieldKind::Option | FieldKind::Switch => {}
-                FieldKind::Positional | FieldKind::SubCommand => errors.err(
+                FieldKind::Positional | FieldKind::SubCommand | FieldKind::Flatten => errors.err(
                     &field,
                     "`global` may only be specified on `#[argp(option)]` \
                      or `#[argp(switch)]` fields",
@@ -194,6 +417,57 @@ impl FieldAttrs {
         check_long_name(errors, long, &value);
     }
 
+    fn parse_attr_delimiter(&mut self, errors: &Errors, m: &syn::MetaNameValue) {
+        if let Some(first) = &self.delimiter {
+            errors.duplicate_attrs("delimiter", first, m);
+        } else if let Some(lit_char) = errors.expect_lit_char(&m.value) {
+            self.delimiter = Some(lit_char.clone());
+            let value = lit_char.value();
+            if !value.is_ascii() || value.is_ascii_alphanumeric() || value == '\\' {
+                errors.err(
+                    lit_char,
+                    "Delimiter must be an ASCII punctuation or whitespace character",
+                );
+            }
+        }
+    }
+
+    fn parse_attr_possible_values(&mut self, errors: &Errors, m: &syn::MetaNameValue) {
+        if self.possible_values.is_some() {
+            return errors.err(m, "Duplicate possible_values attribute");
+        }
+        let array = match &m.value {
+            syn::Expr::Array(array) => array,
+            value => {
+                return errors.err(
+                    value,
+                    "Expected an array of string literals (e.g. `[\"debug\", \"info\"]`)",
+                )
+            }
+        };
+        if array.elems.is_empty() {
+            errors.err(array, "`possible_values` must not be empty");
+        }
+        self.possible_values = Some(
+            array
+                .elems
+                .iter()
+                .filter_map(|elem| errors.expect_lit_str(elem).cloned())
+                .collect(),
+        );
+    }
+
+    fn parse_attr_range(&mut self, errors: &Errors, m: &syn::MetaNameValue) {
+        if let Some(first) = &self.range {
+            errors.duplicate_attrs("range", first, m);
+        } else if let Some(lit_str) = errors.expect_lit_str(&m.value) {
+            match lit_str.parse::<syn::ExprRange>() {
+                Ok(_) => self.range = Some(lit_str.clone()),
+                Err(_) => errors.err(lit_str, "Expected a range (e.g. `1..=256`)"),
+            }
+        }
+    }
+
     fn parse_attr_short(&mut self, errors: &Errors, m: &syn::MetaNameValue) {
         if let Some(first) = &self.short {
             errors.duplicate_attrs("short", first, m);
@@ -233,6 +507,21 @@ pub(crate) fn check_long_name(errors: &Errors, spanned: &impl syn::spanned::Span
     }
 }
 
+/// Checks that the alias of an option is either a long name prefixed with
+/// `--`, or a single ASCII character prefixed with `-`.
+fn check_option_alias(errors: &Errors, alias: &syn::LitStr) {
+    let value = alias.value();
+    if let Some(long) = value.strip_prefix("--").filter(|s| !s.is_empty()) {
+        check_long_name(errors, alias, long);
+    } else if !matches!(value.as_bytes(), [b'-', c] if c.is_ascii() && *c != b'-') {
+        errors.err(
+            alias,
+            "Aliases of options must be a long name prefixed with `--` (e.g. `--colour`), \
+             or a single ASCII character prefixed with `-` (e.g. `-C`)",
+        );
+    }
+}
+
 fn parse_attr_fn_path(
     errors: &Errors,
     m: &syn::MetaList,
@@ -302,6 +591,38 @@ pub struct TypeAttrs {
     pub name: Option<syn::LitStr>,
     pub description: Option<Description>,
     pub footer: Vec<syn::LitStr>,
+    /// The `#[argp(flatten)]` attribute marking a struct that is meant only to
+    /// be flattened into other structs.
+    pub flatten: Option<syn::Path>,
+    pub abbreviations: Option<syn::Path>,
+    pub env_prefix: Option<syn::LitStr>,
+    pub overridable: Option<syn::Path>,
+    pub options_first: Option<syn::Path>,
+    pub response_files: Option<syn::Path>,
+    pub suggestions: Option<syn::Path>,
+    /// The `#[argp(version)]` attribute, either a word (the version of the
+    /// crate) or a name-value with a string literal or a function path.
+    pub version: Option<syn::Meta>,
+    /// The `#[argp(help_command = ...)]` attribute, either a string literal
+    /// with a new name of the `help` subcommand keyword, or `false` to disable
+    /// it.
+    pub help_command: Option<syn::Lit>,
+    /// The `#[argp(help_short = ...)]` attribute, either a character literal
+    /// with a new short name of the help option, or `false` to disable it.
+    pub help_short: Option<syn::Lit>,
+    pub aliases: Vec<syn::LitStr>,
+    pub hidden_aliases: Vec<syn::LitStr>,
+    pub groups: Vec<GroupAttrs>,
+}
+
+/// Represents a `#[argp(group(...))]` type-level attribute declaring a group
+/// of options and switches.
+pub struct GroupAttrs {
+    pub name: syn::LitStr,
+    /// Whether at least one of the group's members must be provided.
+    pub required: bool,
+    /// Whether more than one of the group's members may be provided.
+    pub multiple: bool,
 }
 
 impl TypeAttrs {
@@ -323,35 +644,116 @@ impl TypeAttrs {
 
             for meta in ml {
                 let name = meta.path();
-                if name.is_ident("description") {
+                if name.is_ident("abbreviations") {
+                    parse_attr_word(errors, &meta, "abbreviations", &mut this.abbreviations);
+                } else if name.is_ident("alias") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_multi_string(errors, m, &mut this.aliases);
+                    }
+                } else if name.is_ident("aliases") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_string_array(errors, m, &mut this.aliases);
+                    }
+                } else if name.is_ident("description") {
                     if let Some(m) = errors.expect_meta_name_value(&meta) {
                         parse_attr_description(errors, m, &mut this.description);
                     }
+                } else if name.is_ident("env_prefix") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_single_string(errors, m, "env_prefix", &mut this.env_prefix);
+                    }
+                } else if name.is_ident("flatten") {
+                    parse_attr_word(errors, &meta, "flatten", &mut this.flatten);
                 } else if name.is_ident("footer") {
                     if let Some(m) = errors.expect_meta_name_value(&meta) {
                         parse_attr_multi_string(errors, m, &mut this.footer)
                     }
+                } else if name.is_ident("group") {
+                    if let Some(m) = errors.expect_meta_list(&meta) {
+                        this.parse_attr_group(errors, m);
+                    }
+                } else if name.is_ident("help_command") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        this.parse_attr_help_command(errors, m);
+                    }
+                } else if name.is_ident("help_short") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        this.parse_attr_help_short(errors, m);
+                    }
+                } else if name.is_ident("hidden_alias") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_multi_string(errors, m, &mut this.hidden_aliases);
+                    }
                 } else if name.is_ident("name") {
                     if let Some(m) = errors.expect_meta_name_value(&meta) {
                         this.parse_attr_name(errors, m);
                     }
+                } else if name.is_ident("options_first") {
+                    parse_attr_word(errors, &meta, "options_first", &mut this.options_first);
+                } else if name.is_ident("overridable") {
+                    parse_attr_word(errors, &meta, "overridable", &mut this.overridable);
+                } else if name.is_ident("response_files") {
+                    parse_attr_word(errors, &meta, "response_files", &mut this.response_files);
                 } else if name.is_ident("subcommand") {
                     if let Some(ident) = errors.expect_meta_word(&meta).and_then(|p| p.get_ident())
                     {
                         this.parse_attr_subcommand(errors, ident);
                     }
+                } else if name.is_ident("suggestions") {
+                    parse_attr_word(errors, &meta, "suggestions", &mut this.suggestions);
+                } else if name.is_ident("version") {
+                    this.parse_attr_version(errors, &meta);
                 } else {
                     errors.err(
                         &meta,
                         concat!(
                             "Invalid type-level `argp` attribute\n",
-                            "Expected one of: `description`, `footer`, `name`, `note`, `subcommand`",
+                            "Expected one of: `abbreviations`, `alias`, `aliases`, `description`, ",
+                            "`env_prefix`, `flatten`, `footer`, `group`, `help_command`, `help_short`, ",
+                            "`hidden_alias`, `name`, `note`, ",
+                            "`options_first`, `overridable`, `response_files`, `subcommand`, ",
+                            "`suggestions`, `version`",
                         ),
                     );
                 }
             }
         }
 
+        for alias in this.aliases.iter().chain(&this.hidden_aliases) {
+            if this.is_subcommand.is_none() {
+                errors.err(alias, "Aliases may only be specified on subcommands");
+            } else if alias.value() == "help" {
+                errors.err(alias, "Custom `help` commands are not supported.");
+            } else if alias.value().is_empty() || alias.value().starts_with('-') {
+                errors.err(alias, "Subcommand aliases must not be empty or start with `-`");
+            }
+        }
+
+        if let (Some(flatten), Some(_)) = (&this.flatten, &this.is_subcommand) {
+            errors.err(flatten, "`flatten` cannot be used together with `subcommand`.");
+        }
+        if let (Some(abbreviations), Some(_)) = (&this.abbreviations, &this.is_subcommand) {
+            errors.err(
+                abbreviations,
+                "`abbreviations` may only be used on the top-level command, it's inherited by \
+                 subcommands.",
+            );
+        }
+        if let (Some(suggestions), Some(_)) = (&this.suggestions, &this.is_subcommand) {
+            errors.err(
+                suggestions,
+                "`suggestions` may only be used on the top-level command, it's inherited by \
+                 subcommands.",
+            );
+        }
+        if let (Some(response_files), Some(_)) = (&this.response_files, &this.is_subcommand) {
+            errors.err(
+                response_files,
+                "`response_files` may only be used on the top-level command, the arguments of \
+                 a subcommand are already expanded.",
+            );
+        }
+
         this
     }
 
@@ -364,6 +766,150 @@ impl TypeAttrs {
         }
     }
 
+    fn parse_attr_group(&mut self, errors: &Errors, ml: &syn::MetaList) {
+        let metas = match ml.parse_args_with(
+            syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated,
+        ) {
+            Ok(metas) => metas,
+            Err(e) => return errors.push(e),
+        };
+
+        let mut name = None;
+        let mut required = None;
+        let mut multiple = None;
+
+        for meta in &metas {
+            let meta_name = meta.path();
+            if meta_name.is_ident("multiple") {
+                if let Some(m) = errors.expect_meta_name_value(meta) {
+                    if let Some(first) = &multiple {
+                        errors.duplicate_attrs("multiple", first, m);
+                    } else {
+                        multiple = errors.expect_lit_bool(&m.value).cloned();
+                    }
+                }
+            } else if meta_name.is_ident("name") {
+                if let Some(m) = errors.expect_meta_name_value(meta) {
+                    parse_attr_single_string(errors, m, "name", &mut name);
+                }
+            } else if meta_name.is_ident("required") {
+                parse_attr_word(errors, meta, "required", &mut required);
+            } else {
+                errors.err(
+                    meta,
+                    concat!(
+                        "Invalid `group` attribute\n",
+                        "Expected one of: `multiple`, `name`, `required`",
+                    ),
+                );
+            }
+        }
+
+        let name = if let Some(name) = name {
+            name
+        } else {
+            return errors.err(ml, "Missing `name` of the group");
+        };
+        if let Some(first) = self.groups.iter().find(|g| g.name.value() == name.value()) {
+            return errors.duplicate_attrs("group", &first.name, &name);
+        }
+
+        self.groups.push(GroupAttrs {
+            name,
+            required: required.is_some(),
+            multiple: multiple.map(|m| m.value).unwrap_or(false),
+        });
+    }
+
+    fn parse_attr_help_command(&mut self, errors: &Errors, m: &syn::MetaNameValue) {
+        if let Some(first) = &self.help_command {
+            return errors.duplicate_attrs("help_command", first, m);
+        }
+        match &m.value {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: lit @ syn::Lit::Str(name),
+                ..
+            }) => {
+                if name.value().is_empty() || name.value().starts_with('-') {
+                    errors.err(name, "The help command must not be empty or start with `-`");
+                } else {
+                    self.help_command = Some(lit.clone());
+                }
+            }
+            syn::Expr::Lit(syn::ExprLit {
+                lit: lit @ syn::Lit::Bool(b),
+                ..
+            }) if !b.value => self.help_command = Some(lit.clone()),
+            value => errors.err(value, "Expected a string literal or `false`"),
+        }
+    }
+
+    fn parse_attr_help_short(&mut self, errors: &Errors, m: &syn::MetaNameValue) {
+        if let Some(first) = &self.help_short {
+            return errors.duplicate_attrs("help_short", first, m);
+        }
+        match &m.value {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: lit @ syn::Lit::Char(short),
+                ..
+            }) => {
+                if short.value().is_ascii() {
+                    self.help_short = Some(lit.clone());
+                } else {
+                    errors.err(short, "Short names must be ASCII");
+                }
+            }
+            syn::Expr::Lit(syn::ExprLit {
+                lit: lit @ syn::Lit::Bool(b),
+                ..
+            }) if !b.value => self.help_short = Some(lit.clone()),
+            value => errors.err(value, "Expected a character literal or `false`"),
+        }
+    }
+
+    /// Returns the name of the help subcommand keyword (`help` by default), or
+    /// `None` if it's disabled.
+    pub fn help_command_name(&self) -> Option<String> {
+        match &self.help_command {
+            Some(syn::Lit::Str(name)) => Some(name.value()),
+            Some(_) => None,
+            None => Some("help".to_owned()),
+        }
+    }
+
+    /// Returns the short name of the help option (`h` by default), or `None`
+    /// if it's disabled.
+    pub fn help_short_name(&self) -> Option<char> {
+        match &self.help_short {
+            Some(syn::Lit::Char(short)) => Some(short.value()),
+            Some(_) => None,
+            None => Some('h'),
+        }
+    }
+
+    fn parse_attr_version(&mut self, errors: &Errors, meta: &syn::Meta) {
+        if let Some(first) = &self.version {
+            return errors.duplicate_attrs("version", first, meta);
+        }
+        match meta {
+            syn::Meta::Path(_) => {}
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(_),
+                        ..
+                    })
+                    | syn::Expr::Path(_),
+                ..
+            }) => {}
+            _ => {
+                return errors
+                    .err(meta, "Expected `version`, `version = \"...\"` or `version = path_to_fn`")
+            }
+        }
+        self.version = Some(meta.clone());
+    }
+
     fn parse_attr_subcommand(&mut self, errors: &Errors, ident: &syn::Ident) {
         if let Some(first) = &self.is_subcommand {
             errors.duplicate_attrs("subcommand", first, ident);
@@ -377,6 +923,9 @@ impl TypeAttrs {
 #[derive(Default)]
 pub struct VariantAttrs {
     pub is_dynamic: Option<syn::Path>,
+    /// The name of an inline subcommand (a variant with named fields or a unit
+    /// variant), if specified.
+    pub name: Option<syn::LitStr>,
 }
 
 impl VariantAttrs {
@@ -385,12 +934,14 @@ impl VariantAttrs {
         let mut this = VariantAttrs::default();
 
         let fields = match &variant.fields {
-            syn::Fields::Named(fields) => Some(&fields.named),
-            syn::Fields::Unnamed(fields) => Some(&fields.unnamed),
-            syn::Fields::Unit => None,
+            syn::Fields::Unnamed(fields) => &fields.unnamed,
+            syn::Fields::Named(_) | syn::Fields::Unit => {
+                this.parse_inline(variant);
+                return this;
+            }
         };
 
-        for field in fields.into_iter().flatten() {
+        for field in fields {
             for attr in &field.attrs {
                 if is_argp_attr(attr) {
                     err_unused_enum_attr(errors, attr);
@@ -425,6 +976,126 @@ impl VariantAttrs {
 
         this
     }
+
+    /// Parse `#[argp(...)]` attributes of an inline subcommand. These are
+    /// the same as the type-level attributes of a subcommand struct and they
+    /// are validated when deriving `FromArgs` for the generated struct, so
+    /// only the `name` is extracted here (without reporting errors).
+    fn parse_inline(&mut self, variant: &syn::Variant) {
+        let metas = variant
+            .attrs
+            .iter()
+            .filter(|attr| is_argp_attr(attr))
+            .filter_map(|attr| {
+                attr.parse_args_with(
+                    syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated,
+                )
+                .ok()
+            })
+            .flatten();
+
+        for meta in metas {
+            if let syn::Meta::NameValue(m) = meta {
+                if let (
+                    true,
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(lit),
+                        ..
+                    }),
+                ) = (m.path.is_ident("name"), &m.value)
+                {
+                    self.name.get_or_insert_with(|| lit.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Represents a `#[derive(FromArgValue)]` type's top-level attributes.
+#[derive(Default)]
+pub struct ValueTypeAttrs {
+    pub case_insensitive: Option<syn::Path>,
+}
+
+impl ValueTypeAttrs {
+    /// Parse top-level `#[argp(...)]` attributes
+    pub fn parse(errors: &Errors, derive_input: &syn::DeriveInput) -> Self {
+        let mut this = Self::default();
+
+        for attr in &derive_input.attrs {
+            let ml = if let Some(ml) = argp_attr_to_meta_list(errors, attr) {
+                ml
+            } else {
+                continue;
+            };
+
+            for meta in ml {
+                let name = meta.path();
+                if name.is_ident("case_insensitive") {
+                    parse_attr_word(errors, &meta, "case_insensitive", &mut this.case_insensitive);
+                } else {
+                    errors.err(
+                        &meta,
+                        "Invalid type-level `argp` attribute\n\
+                         Expected one of: `case_insensitive`",
+                    );
+                }
+            }
+        }
+
+        this
+    }
+}
+
+/// Represents a `#[derive(FromArgValue)]` enum variant's attributes.
+#[derive(Default)]
+pub struct ValueVariantAttrs {
+    pub name: Option<syn::LitStr>,
+    pub aliases: Vec<syn::LitStr>,
+}
+
+impl ValueVariantAttrs {
+    /// Parse enum variant `#[argp(...)]` attributes
+    pub fn parse(errors: &Errors, variant: &syn::Variant) -> Self {
+        let mut this = Self::default();
+
+        for attr in &variant.attrs {
+            let ml = if let Some(ml) = argp_attr_to_meta_list(errors, attr) {
+                ml
+            } else {
+                continue;
+            };
+
+            for meta in ml {
+                let name = meta.path();
+                if name.is_ident("alias") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_multi_string(errors, m, &mut this.aliases);
+                    }
+                } else if name.is_ident("name") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_single_string(errors, m, "name", &mut this.name);
+                    }
+                } else {
+                    errors.err(
+                        &meta,
+                        "Invalid variant-level `argp` attribute\n\
+                         Expected one of: `alias`, `name`",
+                    );
+                }
+            }
+        }
+
+        this
+    }
+}
+
+fn parse_attr_word(errors: &Errors, meta: &syn::Meta, name: &str, slot: &mut Option<syn::Path>) {
+    if let Some(first) = slot {
+        errors.duplicate_attrs(name, first, meta);
+    } else {
+        *slot = errors.expect_meta_word(meta).cloned();
+    }
 }
 
 fn parse_attr_single_string(
@@ -446,6 +1117,20 @@ fn parse_attr_multi_string(errors: &Errors, m: &syn::MetaNameValue, list: &mut V
     }
 }
 
+/// Parses an array of string literals (e.g. `["a", "b"]`) and appends them to
+/// the `list`.
+fn parse_attr_string_array(errors: &Errors, m: &syn::MetaNameValue, list: &mut Vec<syn::LitStr>) {
+    match &m.value {
+        syn::Expr::Array(array) => list.extend(
+            array
+                .elems
+                .iter()
+                .filter_map(|elem| errors.expect_lit_str(elem).cloned()),
+        ),
+        value => errors.err(value, "Expected an array of string literals (e.g. `[\"a\", \"b\"]`)"),
+    }
+}
+
 fn parse_attr_doc(errors: &Errors, attr: &syn::Attribute, slot: &mut Option<Description>) {
     let nv = if let Some(nv) = errors.expect_meta_name_value(&attr.meta) {
         nv
@@ -500,6 +1185,19 @@ pub fn check_enum_type_attrs(errors: &Errors, type_attrs: &TypeAttrs, type_span:
         name,
         description,
         footer,
+        flatten,
+        abbreviations,
+        env_prefix,
+        overridable,
+        options_first,
+        response_files,
+        suggestions,
+        version,
+        help_command,
+        help_short,
+        aliases,
+        hidden_aliases,
+        groups,
     } = type_attrs;
 
     // Ensure that `#[argp(subcommand)]` is present.
@@ -525,6 +1223,42 @@ pub fn check_enum_type_attrs(errors: &Errors, type_attrs: &TypeAttrs, type_span:
     if let Some(footer) = footer.first() {
         err_unused_enum_attr(errors, footer);
     }
+    if let Some(flatten) = flatten {
+        err_unused_enum_attr(errors, flatten);
+    }
+    if let Some(abbreviations) = abbreviations {
+        err_unused_enum_attr(errors, abbreviations);
+    }
+    if let Some(env_prefix) = env_prefix {
+        err_unused_enum_attr(errors, env_prefix);
+    }
+    if let Some(overridable) = overridable {
+        err_unused_enum_attr(errors, overridable);
+    }
+    if let Some(options_first) = options_first {
+        err_unused_enum_attr(errors, options_first);
+    }
+    if let Some(response_files) = response_files {
+        err_unused_enum_attr(errors, response_files);
+    }
+    if let Some(suggestions) = suggestions {
+        err_unused_enum_attr(errors, suggestions);
+    }
+    if let Some(version) = version {
+        err_unused_enum_attr(errors, version);
+    }
+    if let Some(help_command) = help_command {
+        err_unused_enum_attr(errors, help_command);
+    }
+    if let Some(help_short) = help_short {
+        err_unused_enum_attr(errors, help_short);
+    }
+    if let Some(alias) = aliases.iter().chain(hidden_aliases).next() {
+        err_unused_enum_attr(errors, alias);
+    }
+    if let Some(group) = groups.first() {
+        err_unused_enum_attr(errors, &group.name);
+    }
 }
 
 fn err_unused_enum_attr(errors: &Errors, location: &impl syn::spanned::Spanned) {